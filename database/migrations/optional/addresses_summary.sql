@@ -0,0 +1,12 @@
+--------------------------------------------------------------
+-- Optional: addresses (first/last seen block_time per address)
+--------------------------------------------------------------
+--     Not backfilled, only addresses indexed after enabling
+--------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS addresses
+(
+    address          VARCHAR PRIMARY KEY,
+    first_block_time BIGINT NOT NULL,
+    last_block_time  BIGINT NOT NULL
+);
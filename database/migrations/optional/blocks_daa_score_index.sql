@@ -0,0 +1,7 @@
+--------------------------------------------------------------
+-- Optional: blocks.daa_score index (prune by daa_score)
+--------------------------------------------------------------
+--     THIS MIGHT TAKE A WHILE ON LARGE DATABASES!
+--------------------------------------------------------------
+
+CREATE INDEX CONCURRENTLY IF NOT EXISTS blocks_daa_score_idx ON blocks (daa_score);
@@ -0,0 +1,7 @@
+--------------------------------------------------------------
+-- Optional: transactions.indexed_at (ingestion time)
+--------------------------------------------------------------
+--     Existing rows will receive the time of this migration
+--------------------------------------------------------------
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS indexed_at TIMESTAMPTZ DEFAULT now();
@@ -4,7 +4,7 @@ CREATE TABLE vars
     value TEXT NOT NULL
 );
 INSERT INTO vars (key, value)
-VALUES ('schema_version', '23');
+VALUES ('schema_version', '29');
 
 
 CREATE TABLE blocks
@@ -24,7 +24,10 @@ CREATE TABLE blocks
     pruning_point           BYTEA,
     "timestamp"             BIGINT,
     utxo_commitment         BYTEA,
-    version                 SMALLINT
+    version                 SMALLINT,
+    merge_set_blues_count   INTEGER,
+    merge_set_reds_count    INTEGER,
+    raw_header              BYTEA
 );
 CREATE INDEX ON blocks (blue_score);
 
@@ -48,7 +51,8 @@ CREATE TYPE transactions_inputs AS
     previous_outpoint_script BYTEA,
     previous_outpoint_amount BIGINT,
     compute_budget           SMALLINT,
-    covenant_id              BYTEA
+    covenant_id              BYTEA,
+    signature_script_length  INTEGER
 );
 
 
@@ -74,7 +78,11 @@ CREATE TABLE transactions
     version        SMALLINT,
     inputs         transactions_inputs[],
     outputs        transactions_outputs[],
-    block_hash     BYTEA
+    block_hash     BYTEA,
+    input_count    SMALLINT,
+    output_count   SMALLINT,
+    payload_compression SMALLINT,
+    fee            BIGINT
 );
 CREATE INDEX ON transactions (block_time DESC);
 
@@ -0,0 +1,12 @@
+--------------------------------------------------------------
+-- v24: Add transactions input/output counts
+--------------------------------------------------------------
+
+-- Nullable without default, no table rewrite. Populated with --enable=transactions_in_out_counts
+ALTER TABLE transactions
+    ADD COLUMN input_count  SMALLINT,
+    ADD COLUMN output_count SMALLINT;
+
+
+-- Update schema_version
+UPDATE vars SET value = '24' WHERE key = 'schema_version';
@@ -0,0 +1,11 @@
+--------------------------------------------------------------
+-- v25: Add transactions payload compression
+--------------------------------------------------------------
+
+-- Nullable without default, no table rewrite. NULL = uncompressed, 1 = zstd. Populated with --compress-payloads
+ALTER TABLE transactions
+    ADD COLUMN payload_compression SMALLINT;
+
+
+-- Update schema_version
+UPDATE vars SET value = '25' WHERE key = 'schema_version';
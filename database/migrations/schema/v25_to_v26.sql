@@ -0,0 +1,11 @@
+--------------------------------------------------------------
+-- v26: Add transactions fee
+--------------------------------------------------------------
+
+-- Nullable without default, no table rewrite. Populated with --enable=transaction_fee
+ALTER TABLE transactions
+    ADD COLUMN fee BIGINT;
+
+
+-- Update schema_version
+UPDATE vars SET value = '26' WHERE key = 'schema_version';
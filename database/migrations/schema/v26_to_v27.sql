@@ -0,0 +1,10 @@
+--------------------------------------------------------------
+-- v27: Add transactions input signature script length
+--------------------------------------------------------------
+
+-- Original length of truncated signature scripts. Populated with --max-signature-script-bytes
+ALTER TYPE transactions_inputs ADD ATTRIBUTE signature_script_length INTEGER;
+
+
+-- Update schema_version
+UPDATE vars SET value = '27' WHERE key = 'schema_version';
@@ -0,0 +1,12 @@
+--------------------------------------------------------------
+-- v28: Add blocks merge set counts
+--------------------------------------------------------------
+
+-- Nullable without default, no table rewrite. Existing rows are left NULL
+ALTER TABLE blocks
+    ADD COLUMN merge_set_blues_count INTEGER,
+    ADD COLUMN merge_set_reds_count  INTEGER;
+
+
+-- Update schema_version
+UPDATE vars SET value = '28' WHERE key = 'schema_version';
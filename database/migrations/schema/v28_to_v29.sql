@@ -0,0 +1,11 @@
+--------------------------------------------------------------
+-- v29: Add blocks raw header
+--------------------------------------------------------------
+
+-- Nullable without default, no table rewrite. Populated with --enable=store_raw_header
+ALTER TABLE blocks
+    ADD COLUMN raw_header BYTEA;
+
+
+-- Update schema_version
+UPDATE vars SET value = '29' WHERE key = 'schema_version';
@@ -1,3 +1,4 @@
+use futures_util::stream::BoxStream;
 use log::{LevelFilter, debug, info, trace, warn};
 use rand::RngExt;
 use regex::Regex;
@@ -5,8 +6,11 @@ use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::{ConnectOptions, Error, Pool, Postgres};
 use std::future::Future;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
+use crate::models::address_summary::AddressSeen;
 use crate::models::address_transaction::AddressTransaction;
 use crate::models::block::Block;
 use crate::models::block_parent::BlockParent;
@@ -18,27 +22,56 @@ use crate::models::transaction_acceptance::TransactionAcceptance;
 use crate::models::types::hash::Hash;
 use crate::query;
 
-const DEADLOCK_MAX_ATTEMPTS: u32 = 3;
+const TRANSIENT_MAX_ATTEMPTS: u32 = 5;
 
 #[derive(Clone)]
 pub struct KaspaDbClient {
     pool: Pool<Postgres>,
+    secondary_pool: Option<Pool<Postgres>>,
+    require_secondary: bool,
+    write_retries: Arc<AtomicU64>,
 }
 
 impl KaspaDbClient {
-    const SCHEMA_VERSION: u8 = 23;
+    const SCHEMA_VERSION: u8 = 29;
+    const CORE_TABLES: &'static [&'static str] =
+        &["blocks", "block_parent", "transactions", "transactions_acceptances", "addresses_transactions", "scripts_transactions"];
+    const SCHEMA_DDL: &'static str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/up.sql"));
 
-    pub async fn new(url: &str, pool_size: u32) -> Result<KaspaDbClient, Error> {
+    /// Returns the DDL of the schema version expected by this binary
+    pub fn schema_ddl() -> (u8, &'static str) {
+        (Self::SCHEMA_VERSION, Self::SCHEMA_DDL)
+    }
+
+    /// Statements exceeding slow_statement_threshold are logged at debug level, a zero threshold disables it
+    pub async fn new(
+        url: &str,
+        pool_size: u32,
+        slow_statement_threshold: Duration,
+        acquire_timeout: Duration,
+    ) -> Result<KaspaDbClient, Error> {
         let url_cleaned = Regex::new(r"(postgres://postgres:)[^@]+(@)").expect("Failed to parse url").replace(url, "$1$2");
         debug!("Connecting to PostgreSQL {}", url_cleaned);
-        let connect_opts = PgConnectOptions::from_str(url)?.log_slow_statements(LevelFilter::Debug, Duration::from_secs(60));
-        let pool = PgPoolOptions::new()
-            .acquire_timeout(Duration::from_secs(30))
-            .max_connections(pool_size)
-            .connect_with(connect_opts)
-            .await?;
+        let connect_opts = if slow_statement_threshold.is_zero() {
+            PgConnectOptions::from_str(url)?.log_slow_statements(LevelFilter::Off, Duration::default())
+        } else {
+            PgConnectOptions::from_str(url)?.log_slow_statements(LevelFilter::Debug, slow_statement_threshold)
+        };
+        let pool = PgPoolOptions::new().acquire_timeout(acquire_timeout).max_connections(pool_size).connect_with(connect_opts).await?;
         info!("Connected to PostgreSQL {}", url_cleaned);
-        Ok(KaspaDbClient { pool })
+        Ok(KaspaDbClient { pool, secondary_pool: None, require_secondary: false, write_retries: Arc::new(AtomicU64::new(0)) })
+    }
+
+    /// Mirrors all writes to the secondary database. Secondary failures are logged unless require_secondary is set
+    pub fn with_secondary(mut self, secondary: KaspaDbClient, require_secondary: bool) -> KaspaDbClient {
+        self.secondary_pool = Some(secondary.pool);
+        self.require_secondary = require_secondary;
+        self
+    }
+
+    /// Number of writes retried after a transient error (pool acquire timeout, deadlock, serialization failure)
+    pub fn write_retries(&self) -> u64 {
+        self.write_retries.load(Ordering::Relaxed)
     }
 
     pub async fn close(&mut self) -> Result<(), Error> {
@@ -194,6 +227,72 @@ impl KaspaDbClient {
                             panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
                         }
                     }
+                    if version == 23 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v23_to_v24.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
+                    if version == 24 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v24_to_v25.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
+                    if version == 25 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v25_to_v26.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
+                    if version == 26 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v26_to_v27.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
+                    if version == 27 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v27_to_v28.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
+                    if version == 28 {
+                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v28_to_v29.sql"));
+                        if upgrade_db {
+                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
+                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
+                            version += 1;
+                        } else {
+                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                        }
+                    }
                     trace!("Schema version is v{version}")
                 }
                 version = self.select_var("schema_version").await?.parse::<u8>().unwrap();
@@ -206,15 +305,38 @@ impl KaspaDbClient {
                 info!("Schema v{} is up to date", version)
             }
             Err(_) => {
+                let existing_tables = self.select_existing_tables(Self::CORE_TABLES).await?;
+                if !existing_tables.is_empty() {
+                    panic!(
+                        "Var 'schema_version' is missing, but schema tables exist ({}). Refusing to apply schema v{}. \
+                        Restore the schema_version var or re-initialize the database with '-c'",
+                        existing_tables.join(", "),
+                        Self::SCHEMA_VERSION
+                    )
+                }
                 warn!("Applying schema v{}", Self::SCHEMA_VERSION);
-                query::misc::execute_ddl(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/up.sql")), &self.pool)
-                    .await?;
+                query::misc::execute_ddl(Self::SCHEMA_DDL, &self.pool).await?;
                 info!("\x1b[32mSchema applied successfully\x1b[0m");
             }
         };
         Ok(())
     }
 
+    pub async fn add_transactions_indexed_at(&self) -> Result<(), Error> {
+        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/optional/transactions_indexed_at.sql"));
+        query::misc::execute_ddl(ddl, &self.pool).await
+    }
+
+    pub async fn add_addresses_summary(&self) -> Result<(), Error> {
+        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/optional/addresses_summary.sql"));
+        query::misc::execute_ddl(ddl, &self.pool).await
+    }
+
+    pub async fn add_blocks_daa_score_index(&self) -> Result<(), Error> {
+        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/optional/blocks_daa_score_index.sql"));
+        query::misc::execute_ddl(ddl, &self.pool).await
+    }
+
     pub async fn drop_schema(&self) -> Result<(), Error> {
         query::misc::execute_ddl(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/down.sql")), &self.pool).await
     }
@@ -227,98 +349,220 @@ impl KaspaDbClient {
         query::select::select_all_table_details(&self.pool).await
     }
 
+    pub async fn select_existing_tables(&self, table_names: &[&str]) -> Result<Vec<String>, Error> {
+        query::select::select_existing_tables(table_names, &self.pool).await
+    }
+
     pub async fn select_var(&self, key: &str) -> Result<String, Error> {
         query::select::select_var(key, &self.pool).await
     }
 
+    pub async fn select_transaction(&self, transaction_id: &Hash) -> Result<Option<Transaction>, Error> {
+        query::select::select_transaction(transaction_id, &self.pool).await
+    }
+
+    pub fn select_transactions_by_block_time(
+        &self,
+        block_time_gte: i64,
+        block_time_lte: i64,
+    ) -> BoxStream<'_, Result<Transaction, Error>> {
+        query::select::select_transactions_by_block_time(block_time_gte, block_time_lte, &self.pool)
+    }
+
+    pub async fn select_transaction_acceptance(&self, transaction_id: &Hash) -> Result<Option<TransactionAcceptance>, Error> {
+        query::select::select_transaction_acceptance(transaction_id, &self.pool).await
+    }
+
+    pub async fn select_is_chain_block(&self, block_hash: &Hash) -> Result<bool, Error> {
+        query::select::select_is_chain_block(block_hash, &self.pool).await
+    }
+
+    pub async fn select_block_timestamp_by_blue_score(&self, blue_score_lte: i64) -> Result<Option<i64>, Error> {
+        query::select::select_block_timestamp_by_blue_score(blue_score_lte, &self.pool).await
+    }
+
+    pub async fn select_recent_transaction_ids(&self, limit: i64) -> Result<Vec<Hash>, Error> {
+        query::select::select_recent_transaction_ids(limit, &self.pool).await
+    }
+
+    pub async fn select_address_tx_count(&self, address: &str, block_time_gte: Option<i64>) -> Result<i64, Error> {
+        query::select::select_address_tx_count(address, block_time_gte, &self.pool).await
+    }
+
+    pub async fn select_missing_block_parents(
+        &self,
+        blue_score_gte: i64,
+        blue_score_lt: i64,
+        limit: i64,
+    ) -> Result<Vec<BlockParent>, Error> {
+        query::select::select_missing_block_parents(blue_score_gte, blue_score_lt, limit, &self.pool).await
+    }
+
     pub async fn insert_blocks(&self, blocks: &[Block]) -> Result<u64, Error> {
-        retry_on_deadlock("blocks", || query::insert::insert_blocks(blocks, &self.pool)).await
+        self.dual_write("blocks", |pool| query::insert::insert_blocks(blocks, pool)).await
     }
 
     pub async fn insert_block_parents(&self, block_parents: &[BlockParent]) -> Result<u64, Error> {
-        retry_on_deadlock("block_parents", || query::insert::insert_block_parents(block_parents, &self.pool)).await
+        self.dual_write("block_parents", |pool| query::insert::insert_block_parents(block_parents, pool)).await
     }
 
     pub async fn insert_transactions(&self, transactions: &[Transaction], upsert_inputs: bool) -> Result<u64, Error> {
-        retry_on_deadlock("transactions", || query::insert::insert_transactions(transactions, upsert_inputs, &self.pool)).await
+        self.dual_write("transactions", |pool| query::insert::insert_transactions(transactions, upsert_inputs, pool)).await
     }
 
     pub async fn insert_address_transactions(&self, address_transactions: &[AddressTransaction]) -> Result<u64, Error> {
-        retry_on_deadlock("addresses_transactions", || query::insert::insert_address_transactions(address_transactions, &self.pool))
-            .await
+        self.dual_write("addresses_transactions", |pool| query::insert::insert_address_transactions(address_transactions, pool)).await
+    }
+
+    pub async fn upsert_address_seen(&self, addresses: &[AddressSeen]) -> Result<u64, Error> {
+        self.dual_write("addresses", |pool| query::insert::upsert_address_seen(addresses, pool)).await
     }
 
     pub async fn insert_script_transactions(&self, script_transactions: &[ScriptTransaction]) -> Result<u64, Error> {
-        retry_on_deadlock("scripts_transactions", || query::insert::insert_script_transactions(script_transactions, &self.pool)).await
+        self.dual_write("scripts_transactions", |pool| query::insert::insert_script_transactions(script_transactions, pool)).await
     }
 
     pub async fn insert_transaction_acceptances(&self, transaction_acceptances: &[TransactionAcceptance]) -> Result<u64, Error> {
-        retry_on_deadlock("transactions_acceptances", || {
-            query::insert::insert_transaction_acceptances(transaction_acceptances, &self.pool)
+        self.dual_write("transactions_acceptances", |pool| {
+            query::insert::insert_transaction_acceptances(transaction_acceptances, pool)
         })
         .await
     }
 
     pub async fn upsert_var(&self, key: &str, value: &String) -> Result<u64, Error> {
-        query::upsert::upsert_var(key, value, &self.pool).await
+        self.dual_write("vars", |pool| query::upsert::upsert_var(key, value, pool)).await
     }
 
     pub async fn delete_transaction_acceptances(&self, block_hashes: &[Hash]) -> Result<u64, Error> {
-        retry_on_deadlock("transactions_acceptances", || query::delete::delete_transaction_acceptances(block_hashes, &self.pool)).await
+        self.dual_write("transactions_acceptances", |pool| query::delete::delete_transaction_acceptances(block_hashes, pool)).await
     }
 
-    pub async fn prune_block_parent(&self, blue_score_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("block_parents (prune)", || query::delete::prune_block_parent(blue_score_lt, batch_size, &self.pool)).await
+    pub async fn prune_block_parent(&self, score_lt: i64, by_daa_score: bool, batch_size: i32) -> Result<u64, Error> {
+        self.dual_write("block_parents (prune)", |pool| query::delete::prune_block_parent(score_lt, by_daa_score, batch_size, pool))
+            .await
     }
 
-    pub async fn prune_transactions_acceptances_using_blocks(&self, blue_score_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("transactions_acceptances (prune by blocks)", || {
-            query::delete::prune_transactions_acceptances_using_blocks(blue_score_lt, batch_size, &self.pool)
+    pub async fn prune_transactions_acceptances_using_blocks(
+        &self,
+        score_lt: i64,
+        by_daa_score: bool,
+        batch_size: i32,
+    ) -> Result<u64, Error> {
+        self.dual_write("transactions_acceptances (prune by blocks)", |pool| {
+            query::delete::prune_transactions_acceptances_using_blocks(score_lt, by_daa_score, batch_size, pool)
         })
         .await
     }
 
     pub async fn prune_transactions_acceptances_using_transactions(&self, block_time_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("transactions_acceptances (prune by transactions)", || {
-            query::delete::prune_transactions_acceptances_using_transactions(block_time_lt, batch_size, &self.pool)
+        self.dual_write("transactions_acceptances (prune by transactions)", |pool| {
+            query::delete::prune_transactions_acceptances_using_transactions(block_time_lt, batch_size, pool)
         })
         .await
     }
 
-    pub async fn prune_blocks(&self, blue_score_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("blocks (prune)", || query::delete::prune_blocks(blue_score_lt, batch_size, &self.pool)).await
+    pub async fn prune_blocks(&self, score_lt: i64, by_daa_score: bool, batch_size: i32) -> Result<u64, Error> {
+        self.dual_write("blocks (prune)", |pool| query::delete::prune_blocks(score_lt, by_daa_score, batch_size, pool)).await
     }
 
     pub async fn prune_transactions(&self, block_time_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("transactions (prune)", || query::delete::prune_transactions(block_time_lt, batch_size, &self.pool)).await
+        self.dual_write("transactions (prune)", |pool| query::delete::prune_transactions(block_time_lt, batch_size, pool)).await
     }
 
     pub async fn prune_addresses_transactions(&self, block_time_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("addresses_transactions (prune)", || {
-            query::delete::prune_addresses_transactions(block_time_lt, batch_size, &self.pool)
+        self.dual_write("addresses_transactions (prune)", |pool| {
+            query::delete::prune_addresses_transactions(block_time_lt, batch_size, pool)
         })
         .await
     }
 
     pub async fn prune_scripts_transactions(&self, block_time_lt: i64, batch_size: i32) -> Result<u64, Error> {
-        retry_on_deadlock("scripts_transactions (prune)", || {
-            query::delete::prune_scripts_transactions(block_time_lt, batch_size, &self.pool)
+        self.dual_write("scripts_transactions (prune)", |pool| {
+            query::delete::prune_scripts_transactions(block_time_lt, batch_size, pool)
         })
         .await
     }
+
+    pub async fn count_prune_block_parent(&self, score_lt: i64, by_daa_score: bool) -> Result<u64, Error> {
+        query::select::count_prune_block_parent(score_lt, by_daa_score, &self.pool).await
+    }
+
+    pub async fn count_prune_transactions_acceptances_using_blocks(&self, score_lt: i64, by_daa_score: bool) -> Result<u64, Error> {
+        query::select::count_prune_transactions_acceptances_using_blocks(score_lt, by_daa_score, &self.pool).await
+    }
+
+    pub async fn count_prune_transactions_acceptances_using_transactions(&self, block_time_lt: i64) -> Result<u64, Error> {
+        query::select::count_prune_transactions_acceptances_using_transactions(block_time_lt, &self.pool).await
+    }
+
+    pub async fn count_prune_blocks(&self, score_lt: i64, by_daa_score: bool) -> Result<u64, Error> {
+        query::select::count_prune_blocks(score_lt, by_daa_score, &self.pool).await
+    }
+
+    pub async fn count_prune_transactions(&self, block_time_lt: i64) -> Result<u64, Error> {
+        query::select::count_prune_transactions(block_time_lt, &self.pool).await
+    }
+
+    pub async fn count_prune_addresses_transactions(&self, block_time_lt: i64) -> Result<u64, Error> {
+        query::select::count_prune_addresses_transactions(block_time_lt, &self.pool).await
+    }
+
+    pub async fn count_prune_scripts_transactions(&self, block_time_lt: i64) -> Result<u64, Error> {
+        query::select::count_prune_scripts_transactions(block_time_lt, &self.pool).await
+    }
+
+    /// Runs read-only referential integrity checks, returns the number of offending rows per check (None if skipped).
+    /// Set transactions_filtered if transactions are intentionally not stored, their acceptances can't be told apart.
+    pub async fn verify_integrity(&self, transactions_filtered: bool) -> Result<Vec<(&'static str, Option<u64>)>, Error> {
+        let acceptances_missing_transaction = if transactions_filtered {
+            None
+        } else {
+            Some(query::select::count_transactions_acceptances_missing_transaction(&self.pool).await?)
+        };
+        Ok(vec![
+            ("transactions.block_hash missing in blocks", Some(query::select::count_transactions_missing_block(&self.pool).await?)),
+            ("transactions_acceptances.transaction_id missing in transactions", acceptances_missing_transaction),
+            (
+                "transactions_acceptances.block_hash missing in blocks",
+                Some(query::select::count_transactions_acceptances_missing_block(&self.pool).await?),
+            ),
+            ("block_parent.block_hash missing in blocks", Some(query::select::count_block_parent_missing_block(&self.pool).await?)),
+            ("inputs.previous_outpoint missing in outputs", Some(query::select::count_inputs_missing_output(&self.pool).await?)),
+        ])
+    }
+
+    async fn dual_write<'a, F, Fut>(&'a self, key: &str, f: F) -> Result<u64, Error>
+    where
+        F: Fn(&'a Pool<Postgres>) -> Fut,
+        Fut: Future<Output = Result<u64, Error>>,
+    {
+        let rows_affected = retry_on_transient(key, &self.write_retries, || f(&self.pool)).await?;
+        if let Some(secondary_pool) = &self.secondary_pool
+            && let Err(e) = retry_on_transient(key, &self.write_retries, || f(secondary_pool)).await
+        {
+            if self.require_secondary {
+                return Err(e);
+            }
+            warn!("Write {key} to secondary database FAILED: {e}");
+        }
+        Ok(rows_affected)
+    }
 }
 
-async fn retry_on_deadlock<F, Fut>(key: &str, mut f: F) -> Result<u64, Error>
+/// Retries writes failing with a transient error, other errors are returned immediately
+async fn retry_on_transient<F, Fut>(key: &str, retries: &AtomicU64, mut f: F) -> Result<u64, Error>
 where
     F: FnMut() -> Fut,
     Fut: Future<Output = Result<u64, Error>>,
 {
-    for attempt in 1..=DEADLOCK_MAX_ATTEMPTS {
+    for attempt in 1..=TRANSIENT_MAX_ATTEMPTS {
         match f().await {
             Ok(n) => return Ok(n),
-            Err(e) if is_deadlock(&e) && attempt < DEADLOCK_MAX_ATTEMPTS => {
-                let backoff_ms = rand::rng().random_range(10..=50);
-                warn!("Deadlock on {key} (attempt {attempt}/{DEADLOCK_MAX_ATTEMPTS}), retrying after {backoff_ms}ms: {e}");
+            Err(e) if attempt < TRANSIENT_MAX_ATTEMPTS && (is_conflict(&e) || matches!(e, Error::PoolTimedOut)) => {
+                // Conflicts resolve quickly, pool exhaustion needs time for running batches to complete
+                let backoff_ms = if is_conflict(&e) { rand::rng().random_range(10..=50) } else { 1000 << (attempt - 1) };
+                warn!("Transient error on {key} (attempt {attempt}/{TRANSIENT_MAX_ATTEMPTS}), retrying after {backoff_ms}ms: {e}");
+                retries.fetch_add(1, Ordering::Relaxed);
                 tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
             }
             Err(e) => return Err(e),
@@ -327,6 +571,7 @@ where
     unreachable!()
 }
 
-fn is_deadlock(e: &Error) -> bool {
-    matches!(e, Error::Database(db) if db.code().as_deref() == Some("40P01"))
+/// Deadlock or serialization failure, the transaction was rolled back and can be retried
+fn is_conflict(e: &Error) -> bool {
+    matches!(e, Error::Database(db) if matches!(db.code().as_deref(), Some("40P01") | Some("40001")))
 }
@@ -0,0 +1,6 @@
+#[derive(Clone)]
+pub struct AddressSeen {
+    pub address: String,
+    pub first_block_time: i64,
+    pub last_block_time: i64,
+}
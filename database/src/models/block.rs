@@ -19,6 +19,9 @@ pub struct Block {
     pub timestamp: Option<i64>,
     pub utxo_commitment: Option<Hash>,
     pub version: Option<i16>,
+    pub merge_set_blues_count: Option<i32>,
+    pub merge_set_reds_count: Option<i32>,
+    pub raw_header: Option<Vec<u8>>,
 }
 
 impl Eq for Block {}
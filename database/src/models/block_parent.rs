@@ -1,6 +1,6 @@
 use crate::models::types::hash::Hash;
 
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Eq, PartialEq, Hash, sqlx::FromRow)]
 pub struct BlockParent {
     pub block_hash: Hash,
     pub parent_hash: Hash,
@@ -1,3 +1,4 @@
+pub mod address_summary;
 pub mod address_transaction;
 pub mod block;
 pub mod block_parent;
@@ -3,7 +3,7 @@ use crate::models::transaction_output::TransactionOutput;
 use crate::models::types::hash::Hash;
 use crate::models::types::payload::Payload;
 
-#[derive(Clone)]
+#[derive(Clone, sqlx::FromRow)]
 pub struct Transaction {
     pub transaction_id: Hash,
     pub subnetwork_id: Option<Vec<u8>>,
@@ -15,6 +15,10 @@ pub struct Transaction {
     pub inputs: Option<Vec<TransactionInput>>,
     pub outputs: Option<Vec<TransactionOutput>>,
     pub block_hash: Option<Hash>,
+    pub input_count: Option<i16>,
+    pub output_count: Option<i16>,
+    pub payload_compression: Option<i16>,
+    pub fee: Option<i64>,
 }
 
 impl Eq for Transaction {}
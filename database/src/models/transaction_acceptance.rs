@@ -1,6 +1,6 @@
 use crate::models::types::hash::Hash;
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq, Hash, sqlx::FromRow)]
 pub struct TransactionAcceptance {
     pub transaction_id: Option<Hash>,
     pub block_hash: Option<Hash>,
@@ -13,4 +13,6 @@ pub struct TransactionInput {
     pub previous_outpoint_amount: Option<i64>,
     pub compute_budget: Option<i16>,
     pub covenant_id: Option<Hash>,
+    /// Original length, only set if signature_script was truncated
+    pub signature_script_length: Option<i32>,
 }
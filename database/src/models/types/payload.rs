@@ -1 +1,21 @@
+use std::io;
+
 pub type Payload = Vec<u8>;
+
+/// Value of transactions.payload_compression for zstd compressed payloads, NULL means uncompressed
+pub const PAYLOAD_COMPRESSION_ZSTD: i16 = 1;
+
+const ZSTD_LEVEL: i32 = 3;
+
+pub fn compress_payload(payload: &[u8]) -> io::Result<Payload> {
+    zstd::bulk::compress(payload, ZSTD_LEVEL)
+}
+
+/// Returns the original payload bytes according to the stored payload_compression value
+pub fn decompress_payload(payload: Payload, compression: Option<i16>) -> io::Result<Payload> {
+    match compression {
+        None => Ok(payload),
+        Some(PAYLOAD_COMPRESSION_ZSTD) => zstd::stream::decode_all(payload.as_slice()),
+        Some(c) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unknown payload compression {c}"))),
+    }
+}
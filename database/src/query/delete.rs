@@ -1,6 +1,6 @@
 use crate::models::types::hash::Hash;
 use log::debug;
-use sqlx::{Error, Pool, Postgres};
+use sqlx::{AssertSqlSafe, Error, Pool, Postgres};
 
 pub async fn delete_transaction_acceptances(block_hashes: &[Hash], pool: &Pool<Postgres>) -> Result<u64, Error> {
     Ok(sqlx::query("DELETE FROM transactions_acceptances WHERE block_hash = ANY($1)")
@@ -10,20 +10,24 @@ pub async fn delete_transaction_acceptances(block_hashes: &[Hash], pool: &Pool<P
         .rows_affected())
 }
 
-pub async fn prune_block_parent(blue_score_lt: i64, batch_size: i32, pool: &Pool<Postgres>) -> Result<u64, Error> {
-    let sql = r#"
+pub async fn prune_block_parent(score_lt: i64, by_daa_score: bool, batch_size: i32, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = format!(
+        r#"
         DELETE FROM block_parent
         WHERE ctid IN (
             SELECT bp.ctid
             FROM block_parent bp
             JOIN blocks b ON bp.block_hash = b.hash
-            WHERE b.blue_score < $1
+            WHERE b.{} < $1
             LIMIT $2
         )
-    "#;
+    "#,
+        score_column(by_daa_score)
+    );
     let mut total_rows_affected: u64 = 0;
     loop {
-        let rows_affected = sqlx::query(sql).bind(blue_score_lt).bind(batch_size).execute(pool).await?.rows_affected();
+        let rows_affected =
+            sqlx::query(AssertSqlSafe(sql.as_str())).bind(score_lt).bind(batch_size).execute(pool).await?.rows_affected();
         if rows_affected == 0 {
             break;
         }
@@ -34,23 +38,28 @@ pub async fn prune_block_parent(blue_score_lt: i64, batch_size: i32, pool: &Pool
 }
 
 pub async fn prune_transactions_acceptances_using_blocks(
-    blue_score_lt: i64,
+    score_lt: i64,
+    by_daa_score: bool,
     batch_size: i32,
     pool: &Pool<Postgres>,
 ) -> Result<u64, Error> {
-    let sql = r#"
+    let sql = format!(
+        r#"
         DELETE FROM transactions_acceptances
         WHERE ctid IN (
             SELECT ta.ctid
             FROM transactions_acceptances ta
             JOIN blocks b ON ta.block_hash = b.hash
-            WHERE b.blue_score < $1
+            WHERE b.{} < $1
             LIMIT $2
         )
-    "#;
+    "#,
+        score_column(by_daa_score)
+    );
     let mut total_rows_affected: u64 = 0;
     loop {
-        let rows_affected = sqlx::query(sql).bind(blue_score_lt).bind(batch_size).execute(pool).await?.rows_affected();
+        let rows_affected =
+            sqlx::query(AssertSqlSafe(sql.as_str())).bind(score_lt).bind(batch_size).execute(pool).await?.rows_affected();
         if rows_affected == 0 {
             break;
         }
@@ -87,19 +96,23 @@ pub async fn prune_transactions_acceptances_using_transactions(
     Ok(total_rows_affected)
 }
 
-pub async fn prune_blocks(blue_score_lt: i64, batch_size: i32, pool: &Pool<Postgres>) -> Result<u64, Error> {
-    let sql = r#"
+pub async fn prune_blocks(score_lt: i64, by_daa_score: bool, batch_size: i32, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = format!(
+        r#"
         DELETE FROM blocks
         WHERE ctid IN (
             SELECT b.ctid
             FROM blocks b
-            WHERE b.blue_score < $1
+            WHERE b.{} < $1
             LIMIT $2
         )
-    "#;
+    "#,
+        score_column(by_daa_score)
+    );
     let mut total_rows_affected: u64 = 0;
     loop {
-        let rows_affected = sqlx::query(sql).bind(blue_score_lt).bind(batch_size).execute(pool).await?.rows_affected();
+        let rows_affected =
+            sqlx::query(AssertSqlSafe(sql.as_str())).bind(score_lt).bind(batch_size).execute(pool).await?.rows_affected();
         if rows_affected == 0 {
             break;
         }
@@ -174,3 +187,7 @@ pub async fn prune_scripts_transactions(block_time_lt: i64, batch_size: i32, poo
     }
     Ok(total_rows_affected)
 }
+
+pub(crate) fn score_column(by_daa_score: bool) -> &'static str {
+    if by_daa_score { "daa_score" } else { "blue_score" }
+}
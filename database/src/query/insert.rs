@@ -1,5 +1,6 @@
 use sqlx::{AssertSqlSafe, Error, Executor, Pool, Postgres};
 
+use crate::models::address_summary::AddressSeen;
 use crate::models::address_transaction::AddressTransaction;
 use crate::models::block::Block;
 use crate::models::block_parent::BlockParent;
@@ -9,13 +10,14 @@ use crate::models::transaction_acceptance::TransactionAcceptance;
 use crate::query::common::generate_placeholders;
 
 pub async fn insert_blocks(blocks: &[Block], pool: &Pool<Postgres>) -> Result<u64, Error> {
-    const COLS: usize = 16;
+    const COLS: usize = 19;
     let mut tx = pool.begin().await?;
 
     let sql = format!(
         "INSERT INTO blocks (hash, accepted_id_merkle_root, transaction_ids,
             merge_set_blues_hashes, merge_set_reds_hashes, selected_parent_hash, bits, blue_score,
-            blue_work, daa_score, hash_merkle_root, nonce, pruning_point, timestamp, utxo_commitment, version
+            blue_work, daa_score, hash_merkle_root, nonce, pruning_point, timestamp, utxo_commitment, version,
+            merge_set_blues_count, merge_set_reds_count, raw_header
         ) VALUES {} ON CONFLICT DO NOTHING",
         generate_placeholders(blocks.len(), COLS)
     );
@@ -38,6 +40,9 @@ pub async fn insert_blocks(blocks: &[Block], pool: &Pool<Postgres>) -> Result<u6
         query = query.bind(block.timestamp);
         query = query.bind(&block.utxo_commitment);
         query = query.bind(block.version);
+        query = query.bind(block.merge_set_blues_count);
+        query = query.bind(block.merge_set_reds_count);
+        query = query.bind(&block.raw_header);
     }
     let rows_affected = tx.execute(query).await?.rows_affected();
     tx.commit().await?;
@@ -60,11 +65,14 @@ pub async fn insert_block_parents(block_parents: &[BlockParent], pool: &Pool<Pos
 }
 
 pub async fn insert_transactions(transactions: &[Transaction], upsert_inputs: bool, pool: &Pool<Postgres>) -> Result<u64, Error> {
-    const COLS: usize = 10;
-    let on_conflict =
-        if upsert_inputs { "ON CONFLICT (transaction_id) DO UPDATE SET inputs = EXCLUDED.inputs" } else { "ON CONFLICT DO NOTHING" };
+    const COLS: usize = 14;
+    let on_conflict = if upsert_inputs {
+        "ON CONFLICT (transaction_id) DO UPDATE SET inputs = EXCLUDED.inputs, fee = COALESCE(EXCLUDED.fee, transactions.fee)"
+    } else {
+        "ON CONFLICT DO NOTHING"
+    };
     let sql = format!(
-        "INSERT INTO transactions (transaction_id, subnetwork_id, hash, mass, payload, block_time, version, inputs, outputs, block_hash)
+        "INSERT INTO transactions (transaction_id, subnetwork_id, hash, mass, payload, block_time, version, inputs, outputs, block_hash, input_count, output_count, payload_compression, fee)
          VALUES {}
          {}",
         generate_placeholders(transactions.len(), COLS),
@@ -83,6 +91,10 @@ pub async fn insert_transactions(transactions: &[Transaction], upsert_inputs: bo
         query = query.bind(&tx.inputs);
         query = query.bind(&tx.outputs);
         query = query.bind(&tx.block_hash);
+        query = query.bind(tx.input_count);
+        query = query.bind(tx.output_count);
+        query = query.bind(tx.payload_compression);
+        query = query.bind(tx.fee);
     }
     Ok(query.execute(pool).await?.rows_affected())
 }
@@ -103,6 +115,25 @@ pub async fn insert_address_transactions(address_transactions: &[AddressTransact
     Ok(query.execute(pool).await?.rows_affected())
 }
 
+pub async fn upsert_address_seen(addresses: &[AddressSeen], pool: &Pool<Postgres>) -> Result<u64, Error> {
+    const COLS: usize = 3;
+    let sql = format!(
+        "INSERT INTO addresses (address, first_block_time, last_block_time)
+        VALUES {} ON CONFLICT (address) DO UPDATE
+        SET first_block_time = LEAST(addresses.first_block_time, EXCLUDED.first_block_time),
+            last_block_time = GREATEST(addresses.last_block_time, EXCLUDED.last_block_time)
+        WHERE EXCLUDED.first_block_time < addresses.first_block_time OR EXCLUDED.last_block_time > addresses.last_block_time",
+        generate_placeholders(addresses.len(), COLS)
+    );
+    let mut query = sqlx::query(AssertSqlSafe(sql));
+    for address in addresses {
+        query = query.bind(&address.address);
+        query = query.bind(address.first_block_time);
+        query = query.bind(address.last_block_time);
+    }
+    Ok(query.execute(pool).await?.rows_affected())
+}
+
 pub async fn insert_script_transactions(script_transactions: &[ScriptTransaction], pool: &Pool<Postgres>) -> Result<u64, Error> {
     const COLS: usize = 3;
     let sql = format!(
@@ -1,6 +1,12 @@
+use crate::models::block_parent::BlockParent;
 use crate::models::query::database_details::DatabaseDetails;
 use crate::models::query::table_details::TableDetails;
-use sqlx::{Error, Pool, Postgres, Row};
+use crate::models::transaction::Transaction;
+use crate::models::transaction_acceptance::TransactionAcceptance;
+use crate::models::types::hash::Hash;
+use crate::query::delete::score_column;
+use futures_util::stream::BoxStream;
+use sqlx::{AssertSqlSafe, Error, Pool, Postgres, Row};
 
 pub async fn select_database_details(pool: &Pool<Postgres>) -> Result<DatabaseDetails, Error> {
     sqlx::query_as::<_, DatabaseDetails>(
@@ -39,6 +45,196 @@ pub async fn select_all_table_details(pool: &Pool<Postgres>) -> Result<Vec<Table
     .await
 }
 
+pub async fn select_existing_tables(table_names: &[&str], pool: &Pool<Postgres>) -> Result<Vec<String>, Error> {
+    sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables
+        WHERE table_schema = current_schema() AND table_name = ANY($1)
+        ORDER BY table_name",
+    )
+    .bind(table_names)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn select_var(key: &str, pool: &Pool<Postgres>) -> Result<String, Error> {
     sqlx::query("SELECT value FROM vars WHERE key = $1").bind(key).fetch_one(pool).await?.try_get(0)
 }
+
+pub async fn select_transaction(transaction_id: &Hash, pool: &Pool<Postgres>) -> Result<Option<Transaction>, Error> {
+    sqlx::query_as::<_, Transaction>(
+        "SELECT transaction_id, subnetwork_id, hash, mass, payload, block_time, version, inputs, outputs, block_hash, input_count, output_count, payload_compression, fee
+        FROM transactions WHERE transaction_id = $1",
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Streams the rows instead of buffering the whole result set
+pub fn select_transactions_by_block_time(
+    block_time_gte: i64,
+    block_time_lte: i64,
+    pool: &Pool<Postgres>,
+) -> BoxStream<'_, Result<Transaction, Error>> {
+    sqlx::query_as::<_, Transaction>(
+        "SELECT transaction_id, subnetwork_id, hash, mass, payload, block_time, version, inputs, outputs, block_hash, input_count, output_count, payload_compression, fee
+        FROM transactions WHERE block_time >= $1 AND block_time <= $2 ORDER BY block_time, transaction_id",
+    )
+    .bind(block_time_gte)
+    .bind(block_time_lte)
+    .fetch(pool)
+}
+
+pub async fn select_transaction_acceptance(
+    transaction_id: &Hash,
+    pool: &Pool<Postgres>,
+) -> Result<Option<TransactionAcceptance>, Error> {
+    sqlx::query_as::<_, TransactionAcceptance>(
+        "SELECT transaction_id, block_hash FROM transactions_acceptances WHERE transaction_id = $1",
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn select_is_chain_block(block_hash: &Hash, pool: &Pool<Postgres>) -> Result<bool, Error> {
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM transactions_acceptances WHERE block_hash = $1)")
+        .bind(block_hash)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn select_block_timestamp_by_blue_score(blue_score_lte: i64, pool: &Pool<Postgres>) -> Result<Option<i64>, Error> {
+    sqlx::query_scalar("SELECT timestamp FROM blocks WHERE blue_score <= $1 ORDER BY blue_score DESC LIMIT 1")
+        .bind(blue_score_lte)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+}
+
+pub async fn select_recent_transaction_ids(limit: i64, pool: &Pool<Postgres>) -> Result<Vec<Hash>, Error> {
+    sqlx::query_scalar("SELECT transaction_id FROM transactions ORDER BY block_time DESC LIMIT $1").bind(limit).fetch_all(pool).await
+}
+
+pub async fn select_address_tx_count(address: &str, block_time_gte: Option<i64>, pool: &Pool<Postgres>) -> Result<i64, Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM addresses_transactions WHERE address = $1 AND ($2::BIGINT IS NULL OR block_time >= $2)")
+        .bind(address)
+        .bind(block_time_gte)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn select_missing_block_parents(
+    blue_score_gte: i64,
+    blue_score_lt: i64,
+    limit: i64,
+    pool: &Pool<Postgres>,
+) -> Result<Vec<BlockParent>, Error> {
+    sqlx::query_as::<_, BlockParent>(
+        "
+        SELECT bp.block_hash, bp.parent_hash
+        FROM blocks b
+        JOIN block_parent bp ON bp.block_hash = b.hash
+        LEFT JOIN blocks p ON p.hash = bp.parent_hash
+        WHERE b.blue_score >= $1 AND b.blue_score < $2 AND p.hash IS NULL
+        LIMIT $3
+    ",
+    )
+    .bind(blue_score_gte)
+    .bind(blue_score_lt)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_prune_block_parent(score_lt: i64, by_daa_score: bool, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = format!(
+        r#"
+        SELECT count(*)
+        FROM block_parent bp
+        JOIN blocks b ON bp.block_hash = b.hash
+        WHERE b.{} < $1
+    "#,
+        score_column(by_daa_score)
+    );
+    Ok(sqlx::query(AssertSqlSafe(sql)).bind(score_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_transactions_acceptances_using_blocks(
+    score_lt: i64,
+    by_daa_score: bool,
+    pool: &Pool<Postgres>,
+) -> Result<u64, Error> {
+    let sql = format!(
+        r#"
+        SELECT count(*)
+        FROM transactions_acceptances ta
+        JOIN blocks b ON ta.block_hash = b.hash
+        WHERE b.{} < $1
+    "#,
+        score_column(by_daa_score)
+    );
+    Ok(sqlx::query(AssertSqlSafe(sql)).bind(score_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_transactions_acceptances_using_transactions(block_time_lt: i64, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = r#"
+        SELECT count(*)
+        FROM transactions_acceptances ta
+        JOIN transactions t ON ta.transaction_id = t.transaction_id
+        WHERE t.block_time < $1
+    "#;
+    Ok(sqlx::query(sql).bind(block_time_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_blocks(score_lt: i64, by_daa_score: bool, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = format!("SELECT count(*) FROM blocks b WHERE b.{} < $1", score_column(by_daa_score));
+    Ok(sqlx::query(AssertSqlSafe(sql)).bind(score_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_transactions(block_time_lt: i64, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM transactions t WHERE t.block_time < $1";
+    Ok(sqlx::query(sql).bind(block_time_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_addresses_transactions(block_time_lt: i64, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM addresses_transactions a WHERE a.block_time < $1";
+    Ok(sqlx::query(sql).bind(block_time_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_prune_scripts_transactions(block_time_lt: i64, pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM scripts_transactions s WHERE s.block_time < $1";
+    Ok(sqlx::query(sql).bind(block_time_lt).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_transactions_missing_block(pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM transactions t
+        WHERE t.block_hash IS NOT NULL AND NOT EXISTS (SELECT 1 FROM blocks b WHERE b.hash = t.block_hash)";
+    Ok(sqlx::query(sql).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_transactions_acceptances_missing_transaction(pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM transactions_acceptances ta
+        WHERE ta.transaction_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM transactions t WHERE t.transaction_id = ta.transaction_id)";
+    Ok(sqlx::query(sql).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_transactions_acceptances_missing_block(pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM transactions_acceptances ta
+        WHERE ta.block_hash IS NOT NULL AND NOT EXISTS (SELECT 1 FROM blocks b WHERE b.hash = ta.block_hash)";
+    Ok(sqlx::query(sql).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_block_parent_missing_block(pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM block_parent bp WHERE NOT EXISTS (SELECT 1 FROM blocks b WHERE b.hash = bp.block_hash)";
+    Ok(sqlx::query(sql).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
+
+pub async fn count_inputs_missing_output(pool: &Pool<Postgres>) -> Result<u64, Error> {
+    let sql = "SELECT count(*) FROM transactions t, unnest(t.inputs) i
+        WHERE i.previous_outpoint_hash IS NOT NULL AND NOT EXISTS (
+            SELECT 1 FROM transactions p
+            WHERE p.transaction_id = i.previous_outpoint_hash AND cardinality(p.outputs) > i.previous_outpoint_index
+        )";
+    Ok(sqlx::query(sql).fetch_one(pool).await?.try_get::<i64, _>(0)? as u64)
+}
@@ -8,7 +8,6 @@ use chrono::{DateTime, Utc};
 use crossbeam_queue::ArrayQueue;
 use deadpool::managed::{Object, Pool};
 use kaspa_hashes::Hash as KaspaHash;
-use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_rpc_core::{RpcBlock, RpcTransaction};
 use log::{debug, trace, warn};
 use log::{error, info};
@@ -17,7 +16,7 @@ use simply_kaspa_cli::cli_args::CliDisable;
 use simply_kaspa_kaspad::manager::KaspadManager;
 use simply_kaspa_signal::signal_handler::SignalHandler;
 use tokio::sync::RwLock;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
 
 #[derive(Debug)]
 pub struct BlockData {
@@ -36,12 +35,16 @@ pub struct TransactionData {
 pub struct KaspaBlocksFetcher {
     disable_transaction_processing: bool,
     poll_interval: Duration,
+    queue_poll_interval: Duration,
+    rpc_timeout: Duration,
     signal_handler: SignalHandler,
     metrics: Arc<RwLock<Metrics>>,
     kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
     blocks_queue: Arc<ArrayQueue<BlockData>>,
     txs_queue: Arc<ArrayQueue<TransactionData>>,
     low_hash: KaspaHash,
+    backfill_to: Option<KaspaHash>,
+    backfill_done: bool,
     last_sync_check: Instant,
     synced: bool,
     lag_count: i32,
@@ -60,19 +63,23 @@ impl KaspaBlocksFetcher {
         blocks_queue: Arc<ArrayQueue<BlockData>>,
         txs_queue: Arc<ArrayQueue<TransactionData>>,
     ) -> KaspaBlocksFetcher {
-        let ttl = settings.cli_args.cache_ttl;
+        let ttl = settings.cli_args.block_cache_ttl();
         let cache_size = settings.net_bps as u64 * ttl * 2;
         let block_cache: Cache<KaspaHash, ()> =
             Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(cache_size).build();
         KaspaBlocksFetcher {
             disable_transaction_processing: settings.cli_args.is_disabled(CliDisable::TransactionProcessing),
             poll_interval: Duration::from_millis(settings.cli_args.block_interval),
+            queue_poll_interval: Duration::from_millis(settings.cli_args.queue_poll_ms),
+            rpc_timeout: Duration::from_secs(settings.cli_args.rpc_timeout_secs),
             signal_handler,
             metrics,
             kaspad_pool,
             blocks_queue,
             txs_queue,
             low_hash: settings.block_checkpoint,
+            backfill_to: settings.backfill_to,
+            backfill_done: false,
             last_sync_check: Instant::now() - Self::SYNC_CHECK_INTERVAL,
             synced: false,
             lag_count: 0,
@@ -85,65 +92,89 @@ impl KaspaBlocksFetcher {
         let start_time = Instant::now();
 
         while !self.signal_handler.is_shutdown() {
+            if self.backfill_done {
+                self.signal_handler.shutdown(&format!("Backfill reached block {}", self.low_hash));
+                return;
+            }
+            if self.backfill_to.is_some() && self.synced {
+                error!("Backfill reached the tip without finding block {}", self.backfill_to.unwrap());
+                self.signal_handler.shutdown("Backfill reached the tip");
+                return;
+            }
             let last_fetch_time = Instant::now();
             debug!("Getting blocks with low_hash {}", self.low_hash);
-            match self.kaspad_pool.get().await {
-                Ok(kaspad) => match kaspad.get_blocks(Some(self.low_hash), true, !self.disable_transaction_processing).await {
-                    Ok(response) => {
-                        debug!("Received {} blocks", response.blocks.len());
-                        trace!("Block hashes: \n{:#?}", response.block_hashes);
-                        let blocks = response.blocks;
-                        let blocks_len = blocks.len();
-                        if !self.synced
-                            && blocks_len < 100
-                            && Instant::now().duration_since(self.last_sync_check) >= Self::SYNC_CHECK_INTERVAL
-                        {
-                            info!("Getting tip hashes from BlockDagInfo for sync check");
-                            if let Ok(block_dag_info) = kaspad.get_block_dag_info().await {
+            let kaspad = match self.kaspad_pool.get().await {
+                Ok(kaspad) => kaspad,
+                Err(e) => {
+                    error!("Failed getting kaspad connection from pool: {}", e);
+                    sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            let result =
+                timeout(self.rpc_timeout, kaspad.get_blocks(Some(self.low_hash), true, !self.disable_transaction_processing)).await;
+            match result {
+                Ok(Ok(response)) => {
+                    debug!("Received {} blocks", response.blocks.len());
+                    trace!("Block hashes: \n{:#?}", response.block_hashes);
+                    let blocks = response.blocks;
+                    let blocks_len = blocks.len();
+                    if !self.synced
+                        && blocks_len < 100
+                        && Instant::now().duration_since(self.last_sync_check) >= Self::SYNC_CHECK_INTERVAL
+                    {
+                        info!("Getting tip hashes from BlockDagInfo for sync check");
+                        let result = timeout(self.rpc_timeout, kaspad.get_block_dag_info()).await;
+                        match result {
+                            Ok(Ok(block_dag_info)) => {
                                 self.tip_hashes = HashSet::from_iter(block_dag_info.tip_hashes);
                                 self.last_sync_check = Instant::now();
                             }
+                            Ok(Err(e)) => warn!("Failed getting BlockDagInfo: {}", e),
+                            Err(_) => self.discard_connection(kaspad, "get_block_dag_info").await,
                         }
-                        let mut txs_len = 0;
-                        if blocks_len > 1 {
-                            let last_block = blocks.last().unwrap().clone();
-                            txs_len = self.handle_blocks(start_time, blocks).await;
+                    }
+                    let mut txs_len = 0;
+                    if blocks_len > 1 {
+                        let last_block = blocks.last().unwrap().clone();
+                        txs_len = self.handle_blocks(start_time, blocks).await;
 
-                            let mut metrics = self.metrics.write().await;
-                            metrics.queues.blocks = self.blocks_queue.len() as u64;
-                            metrics.queues.transactions = self.txs_queue.len() as u64;
-                            metrics.components.block_fetcher.update_last_block(MetricsBlock {
-                                hash: last_block.verbose_data.unwrap().hash.to_string(),
-                                timestamp: last_block.header.timestamp,
-                                date_time: DateTime::from_timestamp_millis(last_block.header.timestamp as i64).unwrap(),
-                                daa_score: last_block.header.daa_score,
-                                blue_score: last_block.header.blue_score,
-                            });
-                        }
-                        let fetch_time = Instant::now().duration_since(last_fetch_time).as_millis() as f64 / 1000f64;
-                        debug!(
-                            "Fetch blocks bps: {:.1}, tps: {:.1} ({:.1} txs/block)",
-                            blocks_len as f64 / fetch_time,
-                            txs_len as f64 / fetch_time,
-                            txs_len as f64 / blocks_len as f64
-                        );
-                        if blocks_len < 50 {
-                            sleep(self.poll_interval).await;
-                        }
+                        let mut metrics = self.metrics.write().await;
+                        metrics.components.block_fetcher.update_last_block(MetricsBlock {
+                            hash: last_block.verbose_data.unwrap().hash.to_string(),
+                            timestamp: last_block.header.timestamp,
+                            date_time: DateTime::from_timestamp_millis(last_block.header.timestamp as i64).unwrap(),
+                            daa_score: last_block.header.daa_score,
+                            blue_score: last_block.header.blue_score,
+                        });
                     }
-                    Err(e) => {
-                        error!("Failed getting blocks with low_hash {}: {}", self.low_hash, e);
-                        sleep(Duration::from_secs(5)).await;
+                    let fetch_time = Instant::now().duration_since(last_fetch_time).as_millis() as f64 / 1000f64;
+                    debug!(
+                        "Fetch blocks bps: {:.1}, tps: {:.1} ({:.1} txs/block)",
+                        blocks_len as f64 / fetch_time,
+                        txs_len as f64 / fetch_time,
+                        txs_len as f64 / blocks_len as f64
+                    );
+                    if blocks_len < 50 {
+                        sleep(self.poll_interval).await;
                     }
-                },
-                Err(e) => {
-                    error!("Failed getting kaspad connection from pool: {}", e);
-                    sleep(Duration::from_secs(5)).await
                 }
+                Ok(Err(e)) => {
+                    error!("Failed getting blocks with low_hash {}: {}", self.low_hash, e);
+                    sleep(Duration::from_secs(5)).await;
+                }
+                Err(_) => self.discard_connection(kaspad, "get_blocks").await,
             }
         }
     }
 
+    /// Removes a (likely half-open) connection from the pool, the next get() establishes a new one
+    async fn discard_connection(&self, kaspad: Object<KaspadManager>, request: &str) {
+        warn!("Kaspad {} request timed out after {}s, reconnecting", request, self.rpc_timeout.as_secs());
+        drop(Object::take(kaspad));
+        self.metrics.write().await.components.block_fetcher.reconnects += 1;
+    }
+
     async fn handle_blocks(&mut self, start_time: Instant, blocks: Vec<RpcBlock>) -> usize {
         let mut txs_len = 0;
         let mut newest_block_timestamp = 0;
@@ -182,7 +213,7 @@ impl KaspaBlocksFetcher {
                     Ok(_) => break,
                     Err(v) => {
                         block_data = v;
-                        sleep(Duration::from_millis(100)).await;
+                        sleep(self.queue_poll_interval).await;
                     }
                 }
             }
@@ -191,11 +222,15 @@ impl KaspaBlocksFetcher {
                     Ok(_) => break,
                     Err(v) => {
                         transaction_data = v;
-                        sleep(Duration::from_millis(100)).await;
+                        sleep(self.queue_poll_interval).await;
                     }
                 }
             }
             self.block_cache.insert(block_hash, ());
+            if self.backfill_to == Some(block_hash) {
+                self.backfill_done = true;
+                break;
+            }
         }
         self.low_hash = block_hash;
         self.lag_count = self.check_lag(self.synced, self.lag_count, newest_block_timestamp);
@@ -4,18 +4,20 @@ use std::time::{Duration, Instant};
 
 use crate::blocks::fetch_blocks::BlockData;
 use crate::checkpoint::{CheckpointBlock, CheckpointOrigin};
+use crate::event_log::EventLog;
 use crate::settings::Settings;
 use crate::web::model::metrics::Metrics;
+use crate::web::model::stream::StreamBlock;
 use chrono::DateTime;
 use crossbeam_queue::ArrayQueue;
-use log::{debug, info, warn};
+use log::{debug, error, info, warn};
 use simply_kaspa_cli::cli_args::CliDisable;
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::block::Block;
 use simply_kaspa_database::models::block_parent::BlockParent;
 use simply_kaspa_mapping::mapper::KaspaDbMapper;
 use simply_kaspa_signal::signal_handler::SignalHandler;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 use tokio::time::sleep;
 
 pub async fn process_blocks(
@@ -26,11 +28,17 @@ pub async fn process_blocks(
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
     mapper: KaspaDbMapper,
+    block_stream: broadcast::Sender<StreamBlock>,
+    event_log: EventLog,
 ) {
     let batch_scale = settings.cli_args.batch_scale;
     let batch_size = (800f64 * batch_scale) as usize;
+    let commit_interval_secs = settings.cli_args.commit_interval_secs;
     let disable_blocks = settings.cli_args.is_disabled(CliDisable::BlocksTable);
     let disable_block_relations = settings.cli_args.is_disabled(CliDisable::BlockParentTable);
+    let abort_on_map_error = settings.cli_args.abort_on_map_error;
+    let limit_blocks = settings.cli_args.limit_blocks;
+    let mut processed_blocks: u64 = 0;
     let mut blocks = vec![];
     let mut blocks_parents = vec![];
     let mut checkpoint_blocks = vec![];
@@ -39,7 +47,16 @@ pub async fn process_blocks(
     while !signal_handler.is_shutdown() {
         if let Some(block_data) = rpc_blocks_queue.pop() {
             if !disable_blocks {
-                blocks.push(mapper.map_block(&block_data.block));
+                match mapper.map_block(&block_data.block) {
+                    Ok(block) => blocks.push(block),
+                    Err(e) if abort_on_map_error => panic!("Mapping block {} FAILED: {e}", block_data.block.header.hash),
+                    Err(e) => {
+                        // The checkpoint block is still registered, a skipped block must not stall checkpointing
+                        error!("Mapping block {} FAILED, skipping it: {e}", block_data.block.header.hash);
+                        let mut metrics = metrics.write().await;
+                        metrics.components.block_processor.add_failed_block(block_data.block.header.hash.to_string());
+                    }
+                }
             }
             if !disable_block_relations {
                 blocks_parents.extend(mapper.map_block_parents(&block_data.block));
@@ -51,56 +68,132 @@ pub async fn process_blocks(
                 daa_score: block_data.block.header.daa_score,
                 blue_score: block_data.block.header.blue_score,
             });
+            processed_blocks += 1;
+            let limit_reached = limit_blocks.is_some_and(|limit| processed_blocks >= limit);
 
-            if checkpoint_blocks.len() >= batch_size
-                || (!checkpoint_blocks.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() > 2)
+            if limit_reached
+                || checkpoint_blocks.len() >= batch_size
+                || (!checkpoint_blocks.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() >= commit_interval_secs)
             {
-                let start_commit_time = Instant::now();
-                debug!("Committing {} blocks ({} parents)", blocks.len(), blocks_parents.len());
-                let last_checkpoint_block = checkpoint_blocks.last().unwrap().clone();
-                let blocks_inserted = if !disable_blocks { insert_blocks(batch_scale, blocks, database.clone()).await } else { 0 };
-                let block_parents_inserted = if !disable_block_relations {
-                    insert_block_parents(batch_scale, blocks_parents, database.clone()).await
-                } else {
-                    0
-                };
-                let last_block_datetime = DateTime::from_timestamp_millis(last_checkpoint_block.timestamp as i64).unwrap();
-
-                if !disable_blocks || !disable_block_relations {
-                    let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
-                    let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
-                    info!(
-                        "Committed {} new blocks in {}ms ({:.1} bps, {} bp). Last block: {}",
-                        blocks_inserted, commit_time, bps, block_parents_inserted, last_block_datetime
-                    );
-                }
-
-                let mut metrics = metrics.write().await;
-                metrics.components.block_processor.update_last_block(last_checkpoint_block.into());
-                drop(metrics);
-
-                for checkpoint_block in checkpoint_blocks {
-                    while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
-                        warn!("Checkpoint queue is full");
-                        sleep(Duration::from_secs(1)).await;
-                    }
-                }
-                blocks = vec![];
-                checkpoint_blocks = vec![];
-                blocks_parents = vec![];
+                commit_blocks(
+                    &settings,
+                    &metrics,
+                    &checkpoint_queue,
+                    &database,
+                    &block_stream,
+                    &event_log,
+                    std::mem::take(&mut blocks),
+                    std::mem::take(&mut blocks_parents),
+                    std::mem::take(&mut checkpoint_blocks),
+                )
+                .await;
                 last_commit_time = Instant::now();
             }
+            if limit_reached {
+                signal_handler.shutdown(&format!("Block limit of {processed_blocks} reached"));
+            }
         } else {
-            sleep(Duration::from_millis(100)).await;
+            sleep(Duration::from_millis(settings.cli_args.queue_poll_ms)).await;
+        }
+    }
+    if !checkpoint_blocks.is_empty() {
+        info!("Committing {} remaining blocks before shutdown", checkpoint_blocks.len());
+        commit_blocks(
+            &settings,
+            &metrics,
+            &checkpoint_queue,
+            &database,
+            &block_stream,
+            &event_log,
+            blocks,
+            blocks_parents,
+            checkpoint_blocks,
+        )
+        .await;
+    }
+}
+
+async fn commit_blocks(
+    settings: &Settings,
+    metrics: &Arc<RwLock<Metrics>>,
+    checkpoint_queue: &ArrayQueue<CheckpointBlock>,
+    database: &KaspaDbClient,
+    block_stream: &broadcast::Sender<StreamBlock>,
+    event_log: &EventLog,
+    blocks: Vec<Block>,
+    blocks_parents: Vec<BlockParent>,
+    checkpoint_blocks: Vec<CheckpointBlock>,
+) {
+    let disable_blocks = settings.cli_args.is_disabled(CliDisable::BlocksTable);
+    let disable_block_relations = settings.cli_args.is_disabled(CliDisable::BlockParentTable);
+    let deterministic = settings.cli_args.deterministic_inserts;
+
+    let start_commit_time = Instant::now();
+    debug!("Committing {} blocks ({} parents)", blocks.len(), blocks_parents.len());
+    let last_checkpoint_block = checkpoint_blocks.last().unwrap().clone();
+    let blocks_inserted = if !disable_blocks {
+        insert_blocks(settings.cli_args.batch_scale_blocks(), deterministic, blocks, database.clone()).await
+    } else {
+        0
+    };
+    let blocks_time = start_commit_time.elapsed().as_millis();
+    let block_parents_inserted = if !disable_block_relations {
+        insert_block_parents(settings.cli_args.batch_scale_block_parents(), deterministic, blocks_parents, database.clone()).await
+    } else {
+        0
+    };
+    let block_parents_time = start_commit_time.elapsed().as_millis() - blocks_time;
+    let last_block_datetime = DateTime::from_timestamp_millis(last_checkpoint_block.timestamp as i64).unwrap();
+
+    if !disable_blocks || !disable_block_relations {
+        let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
+        let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
+        info!(
+            "Committed {} new blocks in {}ms ({:.1} bps, {} bp). Last block: {}",
+            blocks_inserted, commit_time, bps, block_parents_inserted, last_block_datetime
+        );
+        if let Some(slow_commit_warn_ms) = settings.cli_args.slow_commit_warn_ms
+            && commit_time > slow_commit_warn_ms as u128
+        {
+            warn!(
+                "Slow block commit: {}ms exceeds {}ms (blocks: {} rows in {}ms, block_parent: {} rows in {}ms)",
+                commit_time, slow_commit_warn_ms, blocks_inserted, blocks_time, block_parents_inserted, block_parents_time
+            );
+        }
+        event_log.log_commit(
+            "blocks",
+            &[("blocks", blocks_inserted), ("block_parent", block_parents_inserted)],
+            commit_time,
+            &last_checkpoint_block,
+        );
+    }
+
+    let mut metrics = metrics.write().await;
+    metrics.components.block_processor.blocks_committed += checkpoint_blocks.len() as u64;
+    metrics.components.block_processor.update_last_block(last_checkpoint_block.into());
+    drop(metrics);
+
+    for checkpoint_block in checkpoint_blocks {
+        // Never blocks, lagging subscribers are dropped by the stream endpoint
+        if block_stream.receiver_count() > 0 {
+            let _ = block_stream.send(checkpoint_block.clone().into());
+        }
+        while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
+            warn!("Checkpoint queue is full");
+            sleep(Duration::from_millis(settings.cli_args.queue_full_wait_ms)).await;
         }
     }
 }
 
-async fn insert_blocks(batch_scale: f64, values: Vec<Block>, database: KaspaDbClient) -> u64 {
-    let batch_size = min((550f64 * batch_scale) as usize, 4000); // 16 cols: 4000*16=64000 < 65535
+async fn insert_blocks(batch_scale: f64, deterministic: bool, values: Vec<Block>, database: KaspaDbClient) -> u64 {
+    let batch_size = min((550f64 * batch_scale) as usize, 3400); // 19 cols: 3400*19=64600 < 65535
     let key = "blocks";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
+    let mut values = values;
+    if deterministic {
+        sort_blocks(&mut values);
+    }
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
         rows_affected += database.insert_blocks(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
@@ -109,11 +202,15 @@ async fn insert_blocks(batch_scale: f64, values: Vec<Block>, database: KaspaDbCl
     rows_affected
 }
 
-async fn insert_block_parents(batch_scale: f64, values: Vec<BlockParent>, database: KaspaDbClient) -> u64 {
+async fn insert_block_parents(batch_scale: f64, deterministic: bool, values: Vec<BlockParent>, database: KaspaDbClient) -> u64 {
     let batch_size = min((1300f64 * batch_scale) as usize, 32000); // 2 cols: 32000*2=64000 < 65535
     let key = "block_parents";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
+    let mut values = values;
+    if deterministic {
+        sort_block_parents(&mut values);
+    }
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
         rows_affected += database.insert_block_parents(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
@@ -121,3 +218,68 @@ async fn insert_block_parents(batch_scale: f64, values: Vec<BlockParent>, databa
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
+
+fn sort_blocks(values: &mut [Block]) {
+    values.sort_by(|a, b| a.hash.cmp(&b.hash));
+}
+
+fn sort_block_parents(values: &mut [BlockParent]) {
+    values.sort_by(|a, b| a.block_hash.cmp(&b.block_hash).then(a.parent_hash.cmp(&b.parent_hash)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaspa_hashes::Hash as KaspaHash;
+    use simply_kaspa_database::models::types::hash::Hash;
+
+    fn hash(i: u8) -> Hash {
+        KaspaHash::from_bytes([i; 32]).into()
+    }
+
+    fn block(i: u8) -> Block {
+        Block {
+            hash: hash(i),
+            accepted_id_merkle_root: None,
+            transaction_ids: None,
+            merge_set_blues_hashes: None,
+            merge_set_reds_hashes: None,
+            selected_parent_hash: None,
+            bits: None,
+            blue_score: None,
+            blue_work: None,
+            daa_score: None,
+            hash_merkle_root: None,
+            nonce: None,
+            pruning_point: None,
+            timestamp: None,
+            utxo_commitment: None,
+            version: None,
+            merge_set_blues_count: None,
+            merge_set_reds_count: None,
+            raw_header: None,
+        }
+    }
+
+    #[test]
+    fn sort_blocks_is_independent_of_input_order() {
+        let mut a: Vec<Block> = [3, 1, 4, 0, 2].into_iter().map(block).collect();
+        let mut b: Vec<Block> = [2, 0, 4, 1, 3].into_iter().map(block).collect();
+        sort_blocks(&mut a);
+        sort_blocks(&mut b);
+        let hashes = |v: &[Block]| v.iter().map(|b| b.hash.to_string()).collect::<Vec<_>>();
+        assert_eq!(hashes(&a), hashes(&b));
+        assert_eq!(hashes(&a), (0..5).map(|i| hash(i).to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sort_block_parents_is_independent_of_input_order() {
+        let parent = |b: u8, p: u8| BlockParent { block_hash: hash(b), parent_hash: hash(p) };
+        let mut a = vec![parent(2, 1), parent(1, 0), parent(2, 0), parent(1, 2)];
+        let mut b = vec![parent(1, 2), parent(2, 0), parent(1, 0), parent(2, 1)];
+        sort_block_parents(&mut a);
+        sort_block_parents(&mut b);
+        assert!(a == b);
+        assert!(a == vec![parent(1, 0), parent(1, 2), parent(2, 0), parent(2, 1)]);
+    }
+}
@@ -11,6 +11,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -36,6 +37,7 @@ pub async fn process_checkpoints(
     metrics: Arc<RwLock<Metrics>>,
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
+    processors: Vec<JoinHandle<()>>,
 ) {
     let disable_transaction_processing = settings.cli_args.is_disabled(CliDisable::TransactionProcessing);
 
@@ -79,7 +81,11 @@ pub async fn process_checkpoints(
                     cp_ok_txs = true;
                 }
                 txs_processed = HashSet::new();
-                if cp_ok_txs {
+                if cp_ok_txs && settings.backfill_to.is_some() {
+                    debug!("Backfill reached block {}, not saving block_checkpoint", checkpoint_string);
+                    checkpoint_last_saved = Instant::now();
+                    checkpoint_candidate = None;
+                } else if cp_ok_txs {
                     info!("Saving block_checkpoint {}", checkpoint_string);
                     save_block_checkpoint(&checkpoint_string, &database).await.unwrap();
                     let mut metrics = metrics.write().await;
@@ -103,7 +109,55 @@ pub async fn process_checkpoints(
                 }
             }
         } else {
-            sleep(Duration::from_millis(100)).await;
+            sleep(Duration::from_millis(settings.cli_args.queue_poll_ms)).await;
+        }
+    }
+
+    // Wait for the processors to flush their remaining batches, popping meanwhile so they never block on a full queue
+    let mut drained = vec![];
+    for processor in processors {
+        while !processor.is_finished() {
+            while let Some(checkpoint_block) = checkpoint_queue.pop() {
+                drained.push(checkpoint_block);
+            }
+            sleep(Duration::from_millis(settings.cli_args.queue_poll_ms)).await;
+        }
+        processor.await.unwrap();
+    }
+    while let Some(checkpoint_block) = checkpoint_queue.pop() {
+        drained.push(checkpoint_block);
+    }
+
+    // Save the newest fully processed block regardless of CHECKPOINT_SAVE_INTERVAL
+    let mut blocks_processed = vec![];
+    blocks_processed.extend(checkpoint_candidate);
+    for checkpoint_block in drained {
+        match checkpoint_block.origin {
+            CheckpointOrigin::Blocks => blocks_processed.push(checkpoint_block),
+            CheckpointOrigin::Transactions => {
+                txs_processed.insert(checkpoint_block.hash);
+            }
+            CheckpointOrigin::Vcp | CheckpointOrigin::Initial => {}
+        }
+    }
+    let checkpoint = blocks_processed
+        .into_iter()
+        .rev()
+        .find(|checkpoint| disable_transaction_processing || txs_processed.contains(&checkpoint.hash));
+    match checkpoint {
+        Some(checkpoint) if settings.backfill_to.is_none() => {
+            let checkpoint_string = hex::encode(checkpoint.hash.as_bytes());
+            info!("Saving final block_checkpoint {}", checkpoint_string);
+            match save_block_checkpoint(&checkpoint_string, &database).await {
+                Ok(_) => {
+                    let mut metrics = metrics.write().await;
+                    metrics.block_checkpoint.origin = Some(format!("{:?}", checkpoint.origin));
+                    metrics.block_checkpoint.block = Some(checkpoint.into());
+                }
+                Err(e) => error!("Failed to save final block_checkpoint {}: {}", checkpoint_string, e),
+            }
         }
+        Some(_) => debug!("Backfill in progress, not saving final block_checkpoint"),
+        None => debug!("No fully processed block to save as final block_checkpoint"),
     }
 }
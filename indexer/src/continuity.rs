@@ -0,0 +1,68 @@
+use crate::settings::Settings;
+use crate::web::model::metrics::Metrics;
+use log::{debug, error, info, warn};
+use simply_kaspa_cli::cli_args::CliDisable;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+pub async fn continuity_checker(
+    settings: Settings,
+    signal_handler: SignalHandler,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) {
+    if settings.cli_args.is_disabled(CliDisable::BlocksTable) || settings.cli_args.is_disabled(CliDisable::BlockParentTable) {
+        warn!("Continuity checker requires both blocks_table and block_parent_table, skipping");
+        return;
+    }
+    const CHECK_INTERVAL: u64 = 60;
+    const CHECK_RANGE: u64 = 10000;
+    const START_MARGIN: u64 = 1000; // Parents of the first blocks may precede the start block
+    const TIP_MARGIN_SECS: u64 = 30; // Allows in-flight batches to be committed before checking
+    const MAX_REPORTED: i64 = 100;
+    let net_bps = settings.net_bps as u64;
+
+    let mut checked_blue_score = {
+        let mut metrics = metrics.write().await;
+        metrics.components.continuity_checker.enabled = true;
+        metrics.block_checkpoint.block.as_ref().map(|b| b.blue_score).unwrap_or_default() + START_MARGIN
+    };
+    info!("Continuity checker started from blue_score {}", checked_blue_score);
+
+    let mut last_checked = Instant::now();
+    while !signal_handler.is_shutdown() {
+        if Instant::now().duration_since(last_checked).as_secs() < CHECK_INTERVAL {
+            sleep(Duration::from_secs(1)).await;
+            continue;
+        }
+        last_checked = Instant::now();
+        let last_blue_score = metrics.read().await.components.block_processor.last_block.as_ref().map(|b| b.blue_score);
+        let Some(check_to) = last_blue_score.map(|b| b.saturating_sub(TIP_MARGIN_SECS * net_bps)) else {
+            continue;
+        };
+        while checked_blue_score < check_to && !signal_handler.is_shutdown() {
+            let range_end = check_to.min(checked_blue_score + CHECK_RANGE);
+            match database.select_missing_block_parents(checked_blue_score as i64, range_end as i64, MAX_REPORTED).await {
+                Ok(missing) => {
+                    debug!("Continuity checked blue_score range [{}, {}), {} missing", checked_blue_score, range_end, missing.len());
+                    let mut metrics = metrics.write().await;
+                    for block_parent in missing.iter() {
+                        warn!("Continuity gap: Block {} is missing parent {}", block_parent.block_hash, block_parent.parent_hash);
+                        metrics.components.continuity_checker.last_missing_parent = Some(block_parent.parent_hash.to_string());
+                    }
+                    metrics.components.continuity_checker.missing_parents += missing.len() as u64;
+                    metrics.components.continuity_checker.checked_blue_score = Some(range_end);
+                    checked_blue_score = range_end;
+                }
+                Err(e) => {
+                    error!("Continuity check failed: {e}");
+                    break;
+                }
+            }
+        }
+    }
+}
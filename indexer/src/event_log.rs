@@ -0,0 +1,78 @@
+use crate::checkpoint::CheckpointBlock;
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::mpsc::{SyncSender, TrySendError, sync_channel};
+use std::thread;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitEvent {
+    pub time: DateTime<Utc>,
+    pub batch_type: &'static str,
+    pub rows_affected: BTreeMap<&'static str, u64>,
+    pub commit_ms: u64,
+    pub last_block_time: u64,
+    pub last_block_hash: String,
+}
+
+/// Appends one NDJSON line per committed batch. Writing happens on a dedicated thread, so disk I/O never blocks processing
+#[derive(Clone)]
+pub struct EventLog {
+    sender: Option<SyncSender<CommitEvent>>,
+}
+
+impl EventLog {
+    const QUEUE_SIZE: usize = 1000;
+
+    pub fn disabled() -> EventLog {
+        EventLog { sender: None }
+    }
+
+    pub fn open(path: &str) -> EventLog {
+        // Append mode with one write per line keeps lines intact across restarts
+        let mut file =
+            OpenOptions::new().create(true).append(true).open(path).unwrap_or_else(|e| panic!("Unable to open event log {path}: {e}"));
+        let (sender, receiver) = sync_channel::<CommitEvent>(Self::QUEUE_SIZE);
+        let path = path.to_string();
+        info!("Writing commit events to {path}");
+        thread::spawn(move || {
+            for event in receiver {
+                let mut line = serde_json::to_vec(&event).expect("Serialize commit event FAILED");
+                line.push(b'\n');
+                if let Err(e) = file.write_all(&line) {
+                    error!("Writing to event log {path} FAILED: {e}");
+                }
+            }
+        });
+        EventLog { sender: Some(sender) }
+    }
+
+    pub fn log_commit(
+        &self,
+        batch_type: &'static str,
+        rows_affected: &[(&'static str, u64)],
+        commit_ms: u128,
+        last_block: &CheckpointBlock,
+    ) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let event = CommitEvent {
+            time: Utc::now(),
+            batch_type,
+            rows_affected: rows_affected.iter().copied().collect(),
+            commit_ms: commit_ms as u64,
+            last_block_time: last_block.timestamp,
+            last_block_hash: last_block.hash.to_string(),
+        };
+        match sender.try_send(event) {
+            Ok(_) => {}
+            Err(TrySendError::Full(_)) => warn!("Event log queue is full, dropping {batch_type} commit event"),
+            Err(TrySendError::Disconnected(_)) => error!("Event log writer is gone, dropping {batch_type} commit event"),
+        }
+    }
+}
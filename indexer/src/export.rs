@@ -0,0 +1,34 @@
+use crate::web::model::transaction::TransactionResponse;
+use futures_util::StreamExt;
+use log::info;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::models::types::payload::decompress_payload;
+use std::fs::File;
+use std::io::{BufWriter, Write, stdout};
+use std::time::Instant;
+
+/// Writes one JSON object per transaction (same format as the transactions endpoint), '-' writes to stdout
+pub async fn export_transactions_jsonl(database: &KaspaDbClient, path: &str, block_time_gte: i64, block_time_lte: i64) {
+    let start_time = Instant::now();
+    let mut writer: BufWriter<Box<dyn Write>> = if path == "-" {
+        BufWriter::new(Box::new(stdout()))
+    } else {
+        BufWriter::new(Box::new(File::create(path).unwrap_or_else(|e| panic!("Unable to create export file {path}: {e}"))))
+    };
+    let mut transactions = database.select_transactions_by_block_time(block_time_gte, block_time_lte);
+    let mut count = 0u64;
+    while let Some(transaction) = transactions.next().await {
+        let mut transaction = transaction.unwrap_or_else(|e| panic!("Export transactions FAILED: {e}"));
+        if let Some(payload) = transaction.payload.take() {
+            transaction.payload = Some(
+                decompress_payload(payload, transaction.payload_compression)
+                    .unwrap_or_else(|e| panic!("Decompress payload of {} FAILED: {e}", transaction.transaction_id)),
+            );
+        }
+        serde_json::to_writer(&mut writer, &TransactionResponse::new(transaction, None)).expect("Export write FAILED");
+        writer.write_all(b"\n").expect("Export write FAILED");
+        count += 1;
+    }
+    writer.flush().expect("Export write FAILED");
+    info!("Exported {} transactions in {}ms", count, start_time.elapsed().as_millis());
+}
@@ -1,9 +1,15 @@
 pub mod blocks;
 pub mod checkpoint;
+pub mod continuity;
+pub mod event_log;
+pub mod export;
+pub mod logging;
 pub mod macros;
 pub mod prune;
+pub mod queues;
 pub mod settings;
 pub mod transactions;
 pub mod vars;
 pub mod virtual_chain;
+pub mod watch_addresses;
 pub mod web;
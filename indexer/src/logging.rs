@@ -0,0 +1,84 @@
+use crate::settings::Settings;
+use log::{Log, Metadata, Record, info, warn};
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use std::fs;
+use std::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+/// env_logger wrapper which allows replacing the filter at runtime
+pub struct ReloadableLogger {
+    logger: RwLock<env_logger::Logger>,
+    log_to_stderr: bool,
+    log_no_color: bool,
+}
+
+impl ReloadableLogger {
+    /// Installs the logger globally, logs to stderr when stdout is used for data output
+    pub fn init(filters: &str, log_no_color: bool, log_to_stderr: bool) -> &'static ReloadableLogger {
+        let logger = Self::build(filters, log_no_color, log_to_stderr);
+        let max_level = logger.filter();
+        let logger: &'static ReloadableLogger =
+            Box::leak(Box::new(ReloadableLogger { logger: RwLock::new(logger), log_to_stderr, log_no_color }));
+        log::set_logger(logger).expect("Failed to set logger");
+        log::set_max_level(max_level);
+        logger
+    }
+
+    pub fn reload(&self, filters: &str) {
+        let logger = Self::build(filters, self.log_no_color, self.log_to_stderr);
+        log::set_max_level(logger.filter());
+        *self.logger.write().unwrap() = logger;
+    }
+
+    fn build(filters: &str, log_no_color: bool, log_to_stderr: bool) -> env_logger::Logger {
+        env_logger::Builder::new()
+            .target(if log_to_stderr { env_logger::Target::Stderr } else { env_logger::Target::Stdout })
+            .format_target(false)
+            .format_timestamp_millis()
+            .parse_filters(filters)
+            .write_style(if log_no_color { env_logger::WriteStyle::Never } else { env_logger::WriteStyle::Always })
+            .build()
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.read().unwrap().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.logger.read().unwrap().log(record)
+    }
+
+    fn flush(&self) {
+        self.logger.read().unwrap().flush()
+    }
+}
+
+/// Reloads the log filter on SIGHUP, from log-level-file if set, otherwise log-level
+pub async fn log_reloader(settings: Settings, signal_handler: SignalHandler, logger: &'static ReloadableLogger) {
+    let mut shutdown_rx = signal_handler.subscribe();
+    let mut reload_rx = signal_handler.subscribe_reload();
+    while !signal_handler.is_shutdown() {
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            result = reload_rx.recv() => {
+                if let Err(RecvError::Closed) = result {
+                    break;
+                }
+                let filters = match &settings.cli_args.log_level_file {
+                    Some(path) => match fs::read_to_string(path) {
+                        Ok(contents) => contents.trim().to_string(),
+                        Err(e) => {
+                            warn!("Failed to read log-level-file {}: {}", path, e);
+                            continue;
+                        }
+                    },
+                    None => settings.cli_args.log_level.clone(),
+                };
+                logger.reload(&filters);
+                info!("Log level reloaded: {}", filters);
+            }
+        }
+    }
+}
@@ -1,71 +1,225 @@
+use chrono::DateTime;
 use clap::Parser;
 use crossbeam_queue::ArrayQueue;
 use deadpool::managed::{Object, Pool};
 use futures_util::future::try_join_all;
 use kaspa_hashes::Hash as KaspaHash;
-use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_wrpc_client::prelude::{NetworkId, NetworkType};
 use log::{error, info, trace, warn};
-use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable};
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable, CliField};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_indexer::blocks::fetch_blocks::KaspaBlocksFetcher;
 use simply_kaspa_indexer::blocks::process_blocks::process_blocks;
 use simply_kaspa_indexer::checkpoint::{CheckpointBlock, CheckpointOrigin, process_checkpoints};
+use simply_kaspa_indexer::continuity::continuity_checker;
+use simply_kaspa_indexer::event_log::EventLog;
+use simply_kaspa_indexer::export::export_transactions_jsonl;
+use simply_kaspa_indexer::logging::{ReloadableLogger, log_reloader};
 use simply_kaspa_indexer::prune::pruner;
+use simply_kaspa_indexer::queues::queue_sampler;
 use simply_kaspa_indexer::settings::Settings;
 use simply_kaspa_indexer::transactions::process_transactions::process_transactions;
 use simply_kaspa_indexer::vars::{load_block_checkpoint, load_vcp_checkpoint};
+use simply_kaspa_indexer::virtual_chain::backfill_acceptances::backfill_acceptances;
 use simply_kaspa_indexer::virtual_chain::fetch_virtual_chain::fetch_virtual_chain;
+use simply_kaspa_indexer::virtual_chain::find_chain_block::{find_chain_block_by_timestamp, rewind_chain_blocks};
 use simply_kaspa_indexer::virtual_chain::process_virtual_chain::process_virtual_chain;
+use simply_kaspa_indexer::watch_addresses::watch_addresses_reloader;
 use simply_kaspa_indexer::web::model::metrics::Metrics;
 use simply_kaspa_indexer::web::web_server::WebServer;
 use simply_kaspa_kaspad::manager::KaspadManager;
 use simply_kaspa_mapping::mapper::KaspaDbMapper;
 use simply_kaspa_signal::signal_handler::SignalHandler;
 use std::env;
+use std::process;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{RwLock, broadcast, mpsc};
 use tokio::task;
 
 #[tokio::main]
 async fn main() {
-    println!();
-    println!("**************************************************************");
-    println!("******************** Simply Kaspa Indexer ********************");
-    println!("--------------------------------------------------------------");
-    println!("----- https://github.com/supertypo/simply-kaspa-indexer/ -----");
-    println!("--------------------------------------------------------------");
     let cli_args = CliArgs::parse();
-    configure_logging(&cli_args);
+    if cli_args.print_schema {
+        let (version, ddl) = KaspaDbClient::schema_ddl();
+        println!("-- Simply Kaspa Indexer {}, schema v{version}\n{ddl}", cli_args.version());
+        return;
+    }
+    let export_to_stdout = cli_args.export_jsonl.as_deref() == Some("-");
+    if !export_to_stdout {
+        println!();
+        println!("**************************************************************");
+        println!("******************** Simply Kaspa Indexer ********************");
+        println!("--------------------------------------------------------------");
+        println!("----- https://github.com/supertypo/simply-kaspa-indexer/ -----");
+        println!("--------------------------------------------------------------");
+    }
+    let logger = ReloadableLogger::init(&cli_args.log_level, cli_args.log_no_color, export_to_stdout);
 
     trace!("{:?}", cli_args);
     if cli_args.batch_scale < 0.1 || cli_args.batch_scale > 10.0 {
         panic!("Invalid batch-scale");
     }
+    for (name, scale) in [
+        ("batch-scale-blocks", cli_args.batch_scale_blocks),
+        ("batch-scale-block-parents", cli_args.batch_scale_block_parents),
+        ("batch-scale-transactions", cli_args.batch_scale_transactions),
+        ("batch-scale-addresses", cli_args.batch_scale_addresses),
+        ("batch-scale-acceptances", cli_args.batch_scale_acceptances),
+    ] {
+        if scale.is_some_and(|s| !(0.1..=10.0).contains(&s)) {
+            panic!("Invalid {name}");
+        }
+    }
     if cli_args.batch_concurrency < 1 || cli_args.batch_concurrency > 10 {
         panic!("Invalid batch-concurrency");
     }
+    if cli_args.rpc_pool_size < 1 || cli_args.rpc_pool_size > 50 {
+        panic!("Invalid rpc-pool-size");
+    }
+    if cli_args.vcp_min_tip_distance > cli_args.vcp_max_tip_distance {
+        panic!("Invalid vcp-min-tip-distance, exceeds vcp-max-tip-distance");
+    }
     info!("{} {}", env!("CARGO_PKG_NAME"), cli_args.version());
 
     let network_id = NetworkId::from_str(&cli_args.network).unwrap();
-    let kaspad_manager = KaspadManager { network_id, rpc_url: cli_args.rpc_url.clone() };
-    let kaspad_pool: Pool<KaspadManager> = Pool::builder(kaspad_manager).max_size(10).build().unwrap();
+    let kaspad_manager = KaspadManager::new(network_id, cli_args.rpc_url.clone());
+    let kaspad_pool: Pool<KaspadManager> = Pool::builder(kaspad_manager).max_size(cli_args.rpc_pool_size as usize).build().unwrap();
+    info!("Kaspad RPC pool size: {}", cli_args.rpc_pool_size);
 
-    let pool_size = cli_args.batch_concurrency as u32 * 10;
-    let database = KaspaDbClient::new(&cli_args.database_url, pool_size).await.expect("Database connection FAILED");
+    let pool_size = cli_args.max_batch_concurrency() as u32 * 10;
+    let pools = if cli_args.database_url_secondary.is_some() { 2 } else { 1 };
+    if let Some(max_db_connections) = cli_args.max_db_connections
+        && pool_size * pools > max_db_connections
+    {
+        panic!(
+            "Database pool size ({} = max batch-concurrency * 10 * {pools} pool(s)) exceeds max-db-connections ({max_db_connections}). \
+            Lower --batch-concurrency(-*) or raise --max-db-connections (and Postgres max_connections accordingly)",
+            pool_size * pools
+        );
+    }
+    info!("Database pool size: {pool_size}");
+    let slow_statement_threshold = Duration::from_secs(cli_args.slow_statement_threshold);
+    let acquire_timeout = Duration::from_secs(cli_args.db_acquire_timeout_secs);
+    let mut database = KaspaDbClient::new(&cli_args.database_url, pool_size, slow_statement_threshold, acquire_timeout)
+        .await
+        .expect("Database connection FAILED");
+    if cli_args.verify_integrity {
+        verify_integrity(&cli_args, &database).await;
+        return;
+    }
+    prepare_database(&cli_args, &database).await;
+
+    if let Some(database_url_secondary) = &cli_args.database_url_secondary {
+        let secondary = KaspaDbClient::new(database_url_secondary, pool_size, slow_statement_threshold, acquire_timeout)
+            .await
+            .expect("Secondary database connection FAILED");
+        prepare_database(&cli_args, &secondary).await;
+        info!("Mirroring writes to secondary database (required: {})", cli_args.require_secondary);
+        database = database.with_secondary(secondary, cli_args.require_secondary);
+    }
+
+    if let Some(export_path) = &cli_args.export_jsonl {
+        let (from, to) = (cli_args.export_from_block_time.unwrap(), cli_args.export_to_block_time.unwrap());
+        info!("Exporting transactions with block_time {from} to {to}");
+        export_transactions_jsonl(&database, export_path, from, to).await;
+        return;
+    }
+
+    start_processing(cli_args, kaspad_pool, database, logger).await;
+}
+
+/// Refuses to backfill while another indexer instance keeps saving block checkpoints to the same database
+async fn ensure_no_active_indexer(database: &KaspaDbClient) {
+    const ACTIVE_CHECK_SECS: u64 = 90; // Exceeds the block checkpoint save interval
+    if let Ok(saved_block_checkpoint) = load_block_checkpoint(database).await {
+        info!("Checking for an active indexer instance, this takes {ACTIVE_CHECK_SECS}s");
+        tokio::time::sleep(Duration::from_secs(ACTIVE_CHECK_SECS)).await;
+        if load_block_checkpoint(database).await.is_ok_and(|c| c != saved_block_checkpoint) {
+            panic!(
+                "The block_checkpoint was updated during the check, an active indexer is using this database. Stop it before backfilling"
+            );
+        }
+    }
+}
+
+/// Read-only, the schema is never created or upgraded in this mode
+async fn verify_integrity(cli_args: &CliArgs, database: &KaspaDbClient) {
+    let (expected_version, _) = KaspaDbClient::schema_ddl();
+    match database.select_var("schema_version").await.map(|v| v.parse::<u8>()) {
+        Ok(Ok(version)) if version == expected_version => {}
+        Ok(Ok(version)) => {
+            error!("Schema version v{version} does not match v{expected_version} expected by this version, not verifying integrity");
+            process::exit(1);
+        }
+        Ok(Err(e)) => {
+            error!("Var 'schema_version' is invalid: {e}");
+            process::exit(1);
+        }
+        Err(e) => {
+            error!("Unable to read var 'schema_version': {e}");
+            process::exit(1);
+        }
+    }
+    // Acceptances are stored for transactions which are intentionally not stored when these filters are active
+    let transactions_filtered = cli_args.exclude_subnetwork.as_ref().is_some_and(|s| !s.is_empty())
+        || cli_args.watch_addresses_file.is_some()
+        || cli_args.is_disabled(CliDisable::TransactionsTable);
+    info!("Verifying database integrity, this might take a while");
+    let results = database.verify_integrity(transactions_filtered).await.expect("Integrity check FAILED");
+    let width = results.iter().map(|(check, _)| check.len()).max().unwrap_or(0);
+    println!("{:<width$} | {:>12}", "Check", "Rows");
+    println!("{}", "-".repeat(width + 15));
+    for (check, count) in &results {
+        match count {
+            Some(count) => println!("{:<width$} | {:>12}", check, count),
+            None => println!("{:<width$} | {:>12}", check, "skipped"),
+        }
+    }
+    if transactions_filtered {
+        info!("Skipped transactions_acceptances.transaction_id check, transactions are filtered or not stored");
+    }
+    if results.iter().all(|(_, count)| count.unwrap_or(0) == 0) {
+        info!("\x1b[32mIntegrity check completed, no issues found\x1b[0m");
+    } else {
+        warn!(
+            "Integrity check completed with findings. Note that references across the pruning point/retention are expected to be missing"
+        );
+    }
+}
 
+async fn prepare_database(cli_args: &CliArgs, database: &KaspaDbClient) {
     if cli_args.initialize_db {
         info!("Initializing database");
         database.drop_schema().await.expect("Unable to drop schema");
     }
     database.create_schema(cli_args.upgrade_db).await.expect("Unable to create schema");
-
-    start_processing(cli_args, kaspad_pool, database).await;
+    if cli_args.is_enabled(CliEnable::TransactionsIndexedAt) {
+        database.add_transactions_indexed_at().await.expect("Unable to add transactions.indexed_at");
+    }
+    if cli_args.is_enabled(CliEnable::AddressSummary) {
+        if cli_args.is_disabled(CliDisable::AddressesTransactionsTable) || cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress)
+        {
+            panic!("address_summary requires addresses_transactions, remove the conflicting --disable/--exclude-fields");
+        }
+        database.add_addresses_summary().await.expect("Unable to add addresses table");
+    }
+    if cli_args.pruning.prune_db.is_some() && cli_args.pruning.prune_by_daa_score {
+        if cli_args.is_excluded(CliField::BlockDaaScore) {
+            panic!("prune-by-daa-score requires block_daa_score, remove it from --exclude-fields");
+        }
+        info!("Ensuring index on blocks.daa_score, this might take a while");
+        database.add_blocks_daa_score_index().await.expect("Unable to add index on blocks.daa_score");
+    }
 }
 
-async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>, database: KaspaDbClient) {
+async fn start_processing(
+    cli_args: CliArgs,
+    kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
+    database: KaspaDbClient,
+    logger: &'static ReloadableLogger,
+) {
     let signal_handler = SignalHandler::new().spawn();
 
     let block_dag_info = loop {
@@ -97,6 +251,9 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
             info!("Ignoring self-sends for group: {}", group);
         }
     }
+    if let Some(exclude_subnetwork) = &cli_args.exclude_subnetwork {
+        info!("Exclude subnetwork is set, transactions of the following subnetworks will be skipped: {:?}", exclude_subnetwork);
+    }
 
     let net_bps = match block_dag_info.network {
         NetworkId { network_type: NetworkType::Mainnet, suffix: None } => 10,
@@ -104,9 +261,40 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
     };
     let net_tps_max = net_bps as u16 * 300;
     info!("Assuming {} block(s) per second for cache sizes", net_bps);
+    let vcp_synced_threshold = cli_args.vcp_synced_threshold.unwrap_or(net_bps as u64 * 20);
 
-    let block_checkpoint: KaspaHash;
-    if let Some(ignore_checkpoint) = cli_args.ignore_checkpoint.clone() {
+    if let Some(backfill_acceptances_from) = &cli_args.backfill_acceptances_from {
+        if cli_args.is_disabled(CliDisable::TransactionAcceptance) {
+            panic!("Acceptance backfill requires transaction_acceptance to be enabled");
+        }
+        let from = KaspaHash::from_str(backfill_acceptances_from).expect("Supplied backfill-acceptances-from block hash is invalid");
+        let to = KaspaHash::from_str(cli_args.backfill_acceptances_to.as_ref().unwrap())
+            .expect("Supplied backfill-acceptances-to block hash is invalid");
+        let settings = Settings {
+            cli_args: cli_args.clone(),
+            net_bps,
+            net_tps_max,
+            vcp_synced_threshold,
+            block_checkpoint: from,
+            vcp_checkpoint: from,
+            backfill_to: None,
+        };
+        let mapper = KaspaDbMapper::new(cli_args.clone());
+        if let Err(e) = backfill_acceptances(settings, signal_handler, kaspad_pool, database, mapper, from, to).await {
+            panic!("Acceptance backfill failed: {e}");
+        }
+        return;
+    }
+
+    let backfill_to =
+        cli_args.backfill_to.as_ref().map(|h| KaspaHash::from_str(h).expect("Supplied backfill-to block hash is invalid"));
+
+    let mut block_checkpoint: KaspaHash;
+    if let Some(backfill_from) = cli_args.backfill_from.clone() {
+        block_checkpoint = KaspaHash::from_str(backfill_from.as_str()).expect("Supplied backfill-from block hash is invalid");
+        info!("Backfilling from block {} to block {}", block_checkpoint, backfill_to.unwrap());
+        ensure_no_active_indexer(&database).await;
+    } else if let Some(ignore_checkpoint) = cli_args.ignore_checkpoint.clone() {
         warn!("Checkpoint ignored due to user request (-i). This might lead to inconsistencies.");
         if ignore_checkpoint == "p" {
             block_checkpoint = block_dag_info.pruning_point_hash;
@@ -118,6 +306,16 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
             block_checkpoint = KaspaHash::from_str(ignore_checkpoint.as_str()).expect("Supplied block hash is invalid");
             info!("Starting from user supplied block {}", block_checkpoint);
         }
+    } else if let Some(start_from_timestamp) = &cli_args.start_from_timestamp {
+        warn!("Checkpoint ignored due to user request (--start-from-timestamp). This might lead to inconsistencies.");
+        let timestamp = DateTime::parse_from_rfc3339(start_from_timestamp).expect("Supplied start-from-timestamp is invalid");
+        info!("Searching for the chain block at {}, this might take a while", timestamp);
+        let kaspad = kaspad_pool.get().await.expect("Kaspad connection FAILED");
+        block_checkpoint =
+            find_chain_block_by_timestamp(&kaspad, block_dag_info.pruning_point_hash, timestamp.timestamp_millis().max(0) as u64)
+                .await
+                .expect("Failed to find start block by timestamp");
+        info!("Starting from chain block {} (start-from-timestamp)", block_checkpoint);
     } else if let Ok(saved_block_checkpoint) = load_block_checkpoint(&database).await {
         block_checkpoint = KaspaHash::from_str(saved_block_checkpoint.as_str()).expect("Saved block_checkpoint is invalid!");
         info!("Starting from block_checkpoint {}", block_checkpoint);
@@ -126,8 +324,8 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
         warn!("Block checkpoint not found, starting from pruning_point {}", block_checkpoint);
     }
 
-    let vcp_checkpoint: KaspaHash;
-    if cli_args.ignore_checkpoint.is_some() {
+    let mut vcp_checkpoint: KaspaHash;
+    if cli_args.ignore_checkpoint.is_some() || cli_args.start_from_timestamp.is_some() || backfill_to.is_some() {
         vcp_checkpoint = block_checkpoint;
     } else if let Ok(saved_vcp_checkpoint) = load_vcp_checkpoint(&database).await {
         vcp_checkpoint = KaspaHash::from_str(saved_vcp_checkpoint.as_str()).expect("Saved vcp_checkpoint is invalid!");
@@ -137,22 +335,77 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
         info!("VCP starting from block_checkpoint {}", vcp_checkpoint);
     }
 
-    let fetch_checkpoint_block = async |hash: KaspaHash| -> CheckpointBlock {
-        let block = kaspad_pool.get().await.unwrap().get_block(hash, false).await.expect(
-            "Failed to fetch checkpoint block from kaspad. \
-                The block may have been pruned. Connect to an archive node, \
-                or use --ignore-checkpoint=p to resume from the current pruning point",
-        );
-        CheckpointBlock {
+    let fetch_checkpoint_block = async |hash: KaspaHash| -> Result<CheckpointBlock, String> {
+        let block = kaspad_pool.get().await.unwrap().get_block(hash, false).await.map_err(|e| e.to_string())?;
+        Ok(CheckpointBlock {
             origin: CheckpointOrigin::Initial,
             hash: block.header.hash.into(),
             timestamp: block.header.timestamp,
             daa_score: block.header.daa_score,
             blue_score: block.header.blue_score,
+        })
+    };
+    let saved_checkpoints = cli_args.ignore_checkpoint.is_none() && cli_args.start_from_timestamp.is_none() && backfill_to.is_none();
+    let pruning_point = block_dag_info.pruning_point_hash;
+    let block_checkpoint_block = match fetch_checkpoint_block(block_checkpoint).await {
+        Ok(checkpoint_block) => checkpoint_block,
+        Err(e) if saved_checkpoints && cli_args.auto_reset_stale_checkpoint && block_checkpoint != pruning_point => {
+            // The pruning point must be available, otherwise the failure is not caused by the checkpoint being pruned
+            let pruning_point_block = fetch_checkpoint_block(pruning_point)
+                .await
+                .unwrap_or_else(|e| panic!("Failed to fetch pruning_point block from kaspad: {e}"));
+            error!(
+                "Saved block_checkpoint {} was not found on kaspad ({}), it has likely been pruned. \
+                Resetting to pruning_point {} (--auto-reset-stale-checkpoint), blocks in between will NOT be indexed",
+                block_checkpoint, e, pruning_point
+            );
+            block_checkpoint = pruning_point;
+            vcp_checkpoint = pruning_point;
+            pruning_point_block
+        }
+        Err(e) => panic!(
+            "Failed to fetch checkpoint block {block_checkpoint} from kaspad: {e}. \
+                The block may have been pruned. Connect to an archive node, use --auto-reset-stale-checkpoint \
+                or --ignore-checkpoint=p to resume from the current pruning point"
+        ),
+    };
+    let mut vcp_checkpoint_block = match fetch_checkpoint_block(vcp_checkpoint).await {
+        Ok(checkpoint_block) => checkpoint_block,
+        Err(e) if saved_checkpoints && cli_args.auto_reset_stale_checkpoint => {
+            error!(
+                "Saved vcp_checkpoint {} was not found on kaspad ({}), it has likely been pruned. \
+                Resetting to block_checkpoint {} (--auto-reset-stale-checkpoint)",
+                vcp_checkpoint, e, block_checkpoint
+            );
+            vcp_checkpoint = block_checkpoint;
+            block_checkpoint_block.clone()
         }
+        Err(e) => panic!(
+            "Failed to fetch checkpoint block {vcp_checkpoint} from kaspad: {e}. \
+                The block may have been pruned. Connect to an archive node, use --auto-reset-stale-checkpoint \
+                or --ignore-checkpoint=p to resume from the current pruning point"
+        ),
     };
-    let block_checkpoint_block = fetch_checkpoint_block(block_checkpoint).await;
-    let vcp_checkpoint_block = fetch_checkpoint_block(vcp_checkpoint).await;
+    if vcp_checkpoint_block.blue_score < block_checkpoint_block.blue_score {
+        warn!("Saved vcp_checkpoint {} is older than block_checkpoint, VCP will catch up from its own checkpoint", vcp_checkpoint);
+    }
+    if cli_args.vcp_startup_lookback > 0 && backfill_to.is_none() {
+        let kaspad = kaspad_pool.get().await.expect("Kaspad connection FAILED");
+        let rewind = rewind_chain_blocks(&kaspad, vcp_checkpoint, cli_args.vcp_startup_lookback, pruning_point).await;
+        drop(kaspad);
+        match rewind {
+            Ok((hash, rewound)) if rewound > 0 => match fetch_checkpoint_block(hash).await {
+                Ok(checkpoint_block) => {
+                    info!("VCP starting from chain block {} ({} chain blocks before vcp_checkpoint)", hash, rewound);
+                    vcp_checkpoint = hash;
+                    vcp_checkpoint_block = checkpoint_block;
+                }
+                Err(e) => warn!("Failed to fetch vcp lookback block {}: {}, ignoring --vcp-startup-lookback", hash, e),
+            },
+            Ok(_) => {}
+            Err(e) => warn!("Failed to rewind vcp_checkpoint: {}, ignoring --vcp-startup-lookback", e),
+        }
+    }
 
     let queue_capacity = (cli_args.batch_scale * 1000f64) as usize;
     let blocks_queue = Arc::new(ArrayQueue::new(queue_capacity));
@@ -161,15 +414,28 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
 
     let mapper = KaspaDbMapper::new(cli_args.clone());
 
-    let settings = Settings { cli_args: cli_args.clone(), net_bps, net_tps_max, block_checkpoint, vcp_checkpoint };
+    let settings = Settings {
+        cli_args: cli_args.clone(),
+        net_bps,
+        net_tps_max,
+        vcp_synced_threshold,
+        block_checkpoint,
+        vcp_checkpoint,
+        backfill_to,
+    };
+    let backfill = settings.backfill_to.is_some();
 
     let mut metrics = Metrics::new(env!("CARGO_PKG_NAME").to_string(), cli_args.version(), cli_args.commit_id());
     let mut settings_clone = settings.clone();
     settings_clone.cli_args.rpc_url = settings_clone.cli_args.rpc_url.map(|_| "**hidden**".to_string());
     settings_clone.cli_args.database_url = "**hidden**".to_string();
+    if settings_clone.cli_args.database_url_secondary.is_some() {
+        settings_clone.cli_args.database_url_secondary = Some("**hidden**".to_string());
+    }
     metrics.settings = Some(settings_clone);
     metrics.queues.blocks_capacity = blocks_queue.capacity() as u64;
     metrics.queues.transactions_capacity = txs_queue.capacity() as u64;
+    metrics.queues.checkpoint_capacity = checkpoint_queue.capacity() as u64;
     metrics.block_checkpoint.origin = Some(format!("{:?}", block_checkpoint_block.origin));
     metrics.block_checkpoint.block = Some(block_checkpoint_block.into());
     metrics.vcp_checkpoint.origin = Some(format!("{:?}", vcp_checkpoint_block.origin));
@@ -177,11 +443,12 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
     metrics.components.transaction_processor.enabled = !settings.cli_args.is_disabled(CliDisable::TransactionProcessing);
     metrics.components.virtual_chain_processor.enabled = !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing);
     metrics.components.virtual_chain_processor.only_blocks = settings.cli_args.is_disabled(CliDisable::TransactionAcceptance);
+    metrics.components.virtual_chain_processor.tip_distance_min = settings.cli_args.vcp_min_tip_distance;
+    metrics.components.virtual_chain_processor.tip_distance_max = settings.cli_args.vcp_max_tip_distance;
     let metrics = Arc::new(RwLock::new(metrics));
 
-    let webserver =
-        Arc::new(WebServer::new(settings.clone(), signal_handler.clone(), metrics.clone(), kaspad_pool.clone(), database.clone()));
-    let webserver_task = task::spawn(async move { webserver.run().await.unwrap() });
+    let (block_stream, _) = broadcast::channel(1000);
+    let event_log = settings.cli_args.event_log.as_deref().map(EventLog::open).unwrap_or_else(EventLog::disabled);
 
     let mut block_fetcher = KaspaBlocksFetcher::new(
         settings.clone(),
@@ -192,36 +459,69 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
         txs_queue.clone(),
     );
 
-    let mut tasks = vec![
-        webserver_task,
-        task::spawn(async move { block_fetcher.start().await }),
-        task::spawn(process_blocks(
+    // Not joined, runs until shutdown
+    task::spawn(log_reloader(settings.clone(), signal_handler.clone(), logger));
+    if settings.cli_args.watch_addresses_file.is_some() {
+        task::spawn(watch_addresses_reloader(signal_handler.clone(), mapper.clone()));
+    }
+    task::spawn(queue_sampler(
+        signal_handler.clone(),
+        metrics.clone(),
+        blocks_queue.clone(),
+        txs_queue.clone(),
+        checkpoint_queue.clone(),
+    ));
+
+    let mut processors = vec![task::spawn(process_blocks(
+        settings.clone(),
+        signal_handler.clone(),
+        metrics.clone(),
+        blocks_queue.clone(),
+        checkpoint_queue.clone(),
+        database.clone(),
+        mapper.clone(),
+        block_stream.clone(),
+        event_log.clone(),
+    ))];
+    if !settings.cli_args.is_disabled(CliDisable::TransactionProcessing) {
+        processors.push(task::spawn(process_transactions(
             settings.clone(),
             signal_handler.clone(),
             metrics.clone(),
-            blocks_queue.clone(),
+            txs_queue.clone(),
             checkpoint_queue.clone(),
             database.clone(),
             mapper.clone(),
-        )),
+            event_log.clone(),
+        )))
+    }
+    let mut tasks = vec![
+        task::spawn(async move { block_fetcher.start().await }),
+        // Joins the processors on shutdown before saving the final block_checkpoint
         task::spawn(process_checkpoints(
             settings.clone(),
             signal_handler.clone(),
             metrics.clone(),
             checkpoint_queue.clone(),
             database.clone(),
+            processors,
         )),
     ];
-    if !settings.cli_args.is_disabled(CliDisable::TransactionProcessing) {
-        tasks.push(task::spawn(process_transactions(
+    if !backfill {
+        let webserver = Arc::new(WebServer::new(
             settings.clone(),
             signal_handler.clone(),
             metrics.clone(),
-            txs_queue.clone(),
-            checkpoint_queue.clone(),
+            kaspad_pool.clone(),
             database.clone(),
-            mapper.clone(),
-        )))
+            block_stream,
+        ));
+        tasks.push(task::spawn(async move { webserver.run().await.unwrap() }));
+    }
+    if backfill {
+        try_join_all(tasks).await.unwrap();
+        info!("\x1b[32mBackfill completed\x1b[0m");
+        return;
     }
     if !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing) {
         let (vcp_sender, vcp_receiver) = mpsc::channel(1);
@@ -242,6 +542,10 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
         )));
     }
 
+    if settings.cli_args.is_enabled(CliEnable::ContinuityCheck) {
+        tasks.push(task::spawn(continuity_checker(settings.clone(), signal_handler.clone(), metrics.clone(), database.clone())));
+    }
+
     tasks.push(task::spawn(async move {
         if let Err(e) = pruner(settings.clone(), signal_handler.clone(), metrics.clone(), database.clone()).await {
             error!("Database pruner failed: {e}");
@@ -250,13 +554,3 @@ async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Ob
 
     try_join_all(tasks).await.unwrap();
 }
-
-fn configure_logging(cli_args: &CliArgs) {
-    env_logger::Builder::new()
-        .target(env_logger::Target::Stdout)
-        .format_target(false)
-        .format_timestamp_millis()
-        .parse_filters(&cli_args.log_level)
-        .write_style(if cli_args.log_no_color { env_logger::WriteStyle::Never } else { env_logger::WriteStyle::Always })
-        .init();
-}
@@ -2,6 +2,7 @@ use crate::return_on_shutdown;
 use crate::settings::Settings;
 use crate::web::model::metrics::{Metrics, MetricsComponentDbPrunerResult};
 use chrono::{DateTime, Timelike, Utc};
+use futures_util::{StreamExt, stream};
 use log::{error, info, warn};
 use serde_json::to_string_pretty;
 use simply_kaspa_cli::cli_args::{CliDisable, CliField, PruningConfig};
@@ -9,13 +10,29 @@ use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_signal::signal_handler::SignalHandler;
 use std::collections::HashMap;
 use std::error::Error;
+use std::fs::OpenOptions;
 use std::future::Future;
+use std::io::Write;
 use std::ops::Sub;
+use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{Duration, sleep};
 use tokio_cron_scheduler::{Job, JobScheduler};
 
+type PruneStep = Pin<Box<dyn Future<Output = bool> + Send>>;
+
+/// Columns of the prune report, steps which did not run are left empty
+const REPORT_STEPS: [&str; 7] = [
+    "block_parent",
+    "transactions_acceptances (b)",
+    "transactions_acceptances (t)",
+    "blocks",
+    "transactions",
+    "addresses_transactions",
+    "scripts_transactions",
+];
+
 pub async fn pruner(
     settings: Settings,
     signal_handler: SignalHandler,
@@ -31,13 +48,15 @@ pub async fn pruner(
             metrics_rw.components.db_pruner.enabled = true;
             metrics_rw.components.db_pruner.cron = Some(cron.clone());
 
+            let format_retention =
+                |r| pruning_config.retention_blue_scores.map(|n| format!("{n} blue scores")).or_else(|| format_duration(r));
             let mut retention = HashMap::new();
-            retention.insert("block_parent".to_string(), format_duration(pruning_config.retention_block_parent));
-            retention.insert("blocks".to_string(), format_duration(pruning_config.retention_blocks));
+            retention.insert("block_parent".to_string(), format_retention(pruning_config.retention_block_parent));
+            retention.insert("blocks".to_string(), format_retention(pruning_config.retention_blocks));
             retention
-                .insert("transactions_acceptances".to_string(), format_duration(pruning_config.retention_transactions_acceptances));
-            retention.insert("transactions".to_string(), format_duration(pruning_config.retention_transactions));
-            retention.insert("addresses_transactions".to_string(), format_duration(pruning_config.retention_addresses_transactions));
+                .insert("transactions_acceptances".to_string(), format_retention(pruning_config.retention_transactions_acceptances));
+            retention.insert("transactions".to_string(), format_retention(pruning_config.retention_transactions));
+            retention.insert("addresses_transactions".to_string(), format_retention(pruning_config.retention_addresses_transactions));
             metrics_rw.components.db_pruner.retention = Some(retention);
         }
 
@@ -67,9 +86,10 @@ pub async fn prune(
 ) {
     let cli_args = settings.cli_args.clone();
     let batch_size = settings.cli_args.pruning.prune_batch_size;
+    let dry_run = pruning_config.prune_dry_run;
+    let by_daa_score = pruning_config.prune_by_daa_score;
     let common_start_time = now();
-    let mut step_errors = 0;
-    let (net_bps, checkpoint_blue_score, checkpoint_time) = {
+    let (net_bps, checkpoint_score, checkpoint_time) = {
         let mut metrics_rw = metrics.write().await;
         if metrics_rw.components.db_pruner.running == Some(true) {
             warn!("Database pruning skipped: previous run still in progress");
@@ -79,128 +99,262 @@ pub async fn prune(
         metrics_rw.components.db_pruner.start_time = Some(common_start_time);
         metrics_rw.components.db_pruner.results = Some(HashMap::new());
         let block = metrics_rw.block_checkpoint.block.as_ref().unwrap();
-        (settings.net_bps as u64, block.blue_score, block.date_time)
+        (settings.net_bps as u64, if by_daa_score { block.daa_score } else { block.blue_score }, block.date_time)
     };
-    info!("\x1b[33mDatabase pruning started\x1b[0m");
+    if dry_run {
+        info!("\x1b[33mDatabase pruning started (dry run, nothing will be deleted)\x1b[0m");
+    } else {
+        info!("\x1b[33mDatabase pruning started\x1b[0m");
+    }
 
-    if let Some(retention) = pruning_config.retention_block_parent {
+    let blue_score_cutoff = match pruning_config.retention_blue_scores {
+        Some(blue_scores) => {
+            let cutoff_score = checkpoint_score.saturating_sub(blue_scores) as i64;
+            let cutoff_time = match database.select_block_timestamp_by_blue_score(cutoff_score).await {
+                Ok(Some(timestamp)) => DateTime::from_timestamp_millis(timestamp).unwrap(),
+                result => {
+                    if let Err(e) = result {
+                        warn!("Failed to look up the block_time of blue score {cutoff_score}: {e}");
+                    }
+                    warn!(
+                        "Cutoff block not found, estimating the block_time cutoff for blue score {cutoff_score} using {net_bps} bps"
+                    );
+                    checkpoint_time.sub(Duration::from_secs(blue_scores / net_bps))
+                }
+            };
+            Some((cutoff_score, cutoff_time))
+        }
+        None => None,
+    };
+    // Returns the (score, time) cutoffs, either from the duration retention or the blue score retention
+    let cutoff = |retention: Option<Duration>| -> Option<(i64, DateTime<Utc>)> {
+        blue_score_cutoff
+            .or_else(|| retention.map(|r| (checkpoint_score.saturating_sub(r.as_secs() * net_bps) as i64, checkpoint_time.sub(r))))
+    };
+
+    // Steps are grouped by dependency, the groups are independent and can run concurrently:
+    // block_parent and transactions_acceptances (b) rely on blocks, transactions_acceptances (t) relies on transactions
+    let mut block_steps: Vec<PruneStep> = vec![];
+    let mut transaction_steps: Vec<PruneStep> = vec![];
+    let mut address_steps: Vec<PruneStep> = vec![];
+
+    let retention_block_parent =
+        pruning_config.retention_block_parent.map(|r| r.min(pruning_config.retention_blocks.unwrap_or(Duration::MAX)));
+    if let Some((cutoff_score, cutoff_time)) = cutoff(retention_block_parent) {
         let db = database.clone();
-        let retention = retention.min(pruning_config.retention_blocks.unwrap_or(Duration::MAX));
-        let cutoff_blue_score = checkpoint_blue_score.saturating_sub(retention.as_secs() * net_bps) as i64;
-        let cutoff_time = checkpoint_time.sub(retention);
-        return_on_shutdown!(signal_handler.is_shutdown());
-        step_errors += prune_step(
+        let metrics = metrics.clone();
+        block_steps.push(Box::pin(prune_step(
             "block_parent",
-            metrics.clone(),
-            |(blue_score, _)| async move { db.prune_block_parent(blue_score, batch_size).await },
-            cutoff_blue_score,
+            metrics,
+            dry_run,
+            move |(score, _)| async move {
+                if dry_run {
+                    db.count_prune_block_parent(score, by_daa_score).await
+                } else {
+                    db.prune_block_parent(score, by_daa_score, batch_size).await
+                }
+            },
+            cutoff_score,
             cutoff_time,
-        )
-        .await as i32;
+        )));
     }
 
-    if let Some(retention) = pruning_config.retention_transactions_acceptances {
-        return_on_shutdown!(signal_handler.is_shutdown());
+    let retention = pruning_config.retention_transactions_acceptances;
+    if cutoff(retention).is_some() {
         let db = database.clone();
+        let metrics = metrics.clone();
         if !cli_args.is_disabled(CliDisable::BlocksTable) {
-            let retention = retention.min(pruning_config.retention_blocks.unwrap_or(Duration::MAX));
-            let cutoff_blue_score = checkpoint_blue_score.saturating_sub(retention.as_secs() * net_bps) as i64;
-            let cutoff_time = checkpoint_time.sub(retention);
-            step_errors += prune_step(
+            let retention = retention.map(|r| r.min(pruning_config.retention_blocks.unwrap_or(Duration::MAX)));
+            let (cutoff_score, cutoff_time) = cutoff(retention).unwrap();
+            block_steps.push(Box::pin(prune_step(
                 "transactions_acceptances (b)",
-                metrics.clone(),
-                |(blue_score, _)| async move { db.prune_transactions_acceptances_using_blocks(blue_score, batch_size).await },
-                cutoff_blue_score,
+                metrics,
+                dry_run,
+                move |(score, _)| async move {
+                    if dry_run {
+                        db.count_prune_transactions_acceptances_using_blocks(score, by_daa_score).await
+                    } else {
+                        db.prune_transactions_acceptances_using_blocks(score, by_daa_score, batch_size).await
+                    }
+                },
+                cutoff_score,
                 cutoff_time,
-            )
-            .await as i32;
+            )));
         } else if !cli_args.is_disabled(CliDisable::TransactionsTable)
             && !cli_args.is_disabled(CliDisable::TransactionProcessing)
             && !cli_args.is_disabled(CliDisable::TransactionAcceptance)
         {
-            let retention = retention.min(pruning_config.retention_transactions.unwrap_or(Duration::MAX));
-            let cutoff_time = checkpoint_time.sub(retention);
-            step_errors += prune_step(
+            let retention = retention.map(|r| r.min(pruning_config.retention_transactions.unwrap_or(Duration::MAX)));
+            let (_, cutoff_time) = cutoff(retention).unwrap();
+            transaction_steps.push(Box::pin(prune_step(
                 "transactions_acceptances (t)",
-                metrics.clone(),
-                |(_, time_ms)| async move { db.prune_transactions_acceptances_using_transactions(time_ms, batch_size).await },
+                metrics,
+                dry_run,
+                move |(_, time_ms)| async move {
+                    if dry_run {
+                        db.count_prune_transactions_acceptances_using_transactions(time_ms).await
+                    } else {
+                        db.prune_transactions_acceptances_using_transactions(time_ms, batch_size).await
+                    }
+                },
                 0,
                 cutoff_time,
-            )
-            .await as i32;
+            )));
         }
     }
 
-    if let Some(retention) = pruning_config.retention_blocks {
-        let cutoff_blue_score = checkpoint_blue_score.saturating_sub(retention.as_secs() * net_bps) as i64;
-        let cutoff_time = checkpoint_time.sub(retention);
-        return_on_shutdown!(signal_handler.is_shutdown());
+    if let Some((cutoff_score, cutoff_time)) = cutoff(pruning_config.retention_blocks) {
         let db = database.clone();
-        step_errors += prune_step(
+        let metrics = metrics.clone();
+        block_steps.push(Box::pin(prune_step(
             "blocks",
-            metrics.clone(),
-            |(blue_score, _)| async move { db.prune_blocks(blue_score, batch_size).await },
-            cutoff_blue_score,
+            metrics,
+            dry_run,
+            move |(score, _)| async move {
+                if dry_run {
+                    db.count_prune_blocks(score, by_daa_score).await
+                } else {
+                    db.prune_blocks(score, by_daa_score, batch_size).await
+                }
+            },
+            cutoff_score,
             cutoff_time,
-        )
-        .await as i32;
+        )));
     }
 
-    if let Some(retention) = pruning_config.retention_transactions {
-        let cutoff_time = checkpoint_time.sub(retention);
-        return_on_shutdown!(signal_handler.is_shutdown());
+    if let Some((_, cutoff_time)) = cutoff(pruning_config.retention_transactions) {
         let db = database.clone();
-        step_errors += prune_step(
+        let metrics = metrics.clone();
+        transaction_steps.push(Box::pin(prune_step(
             "transactions",
-            metrics.clone(),
-            |(_, time_ms)| async move { db.prune_transactions(time_ms, batch_size).await },
+            metrics,
+            dry_run,
+            move |(_, time_ms)| async move {
+                if dry_run { db.count_prune_transactions(time_ms).await } else { db.prune_transactions(time_ms, batch_size).await }
+            },
             0,
             cutoff_time,
-        )
-        .await as i32;
+        )));
     }
 
-    if let Some(retention) = pruning_config.retention_addresses_transactions {
-        let cutoff_time = checkpoint_time.sub(retention);
-        return_on_shutdown!(signal_handler.is_shutdown());
+    if let Some((_, cutoff_time)) = cutoff(pruning_config.retention_addresses_transactions) {
         let db = database.clone();
+        let metrics = metrics.clone();
         if !cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress) {
-            step_errors += prune_step(
+            address_steps.push(Box::pin(prune_step(
                 "addresses_transactions",
-                metrics.clone(),
-                |(_, time_ms)| async move { db.prune_addresses_transactions(time_ms, batch_size).await },
+                metrics,
+                dry_run,
+                move |(_, time_ms)| async move {
+                    if dry_run {
+                        db.count_prune_addresses_transactions(time_ms).await
+                    } else {
+                        db.prune_addresses_transactions(time_ms, batch_size).await
+                    }
+                },
                 0,
                 cutoff_time,
-            )
-            .await as i32;
+            )));
         } else {
-            step_errors += prune_step(
+            address_steps.push(Box::pin(prune_step(
                 "scripts_transactions",
-                metrics.clone(),
-                |(_, time_ms)| async move { db.prune_scripts_transactions(time_ms, batch_size).await },
+                metrics,
+                dry_run,
+                move |(_, time_ms)| async move {
+                    if dry_run {
+                        db.count_prune_scripts_transactions(time_ms).await
+                    } else {
+                        db.prune_scripts_transactions(time_ms, batch_size).await
+                    }
+                },
                 0,
                 cutoff_time,
-            )
-            .await as i32;
+            )));
         }
     }
 
+    let step_errors: i32 = stream::iter([block_steps, transaction_steps, address_steps])
+        .map(|steps| prune_steps(steps, &signal_handler))
+        .buffer_unordered(pruning_config.prune_concurrency as usize)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .sum();
+    return_on_shutdown!(signal_handler.is_shutdown());
+
     if step_errors == 0 {
         info!("\x1b[32mDatabase pruning completed successfully!\x1b[0m");
     } else {
         warn!("\x1b[33mDatabase pruning completed with one or more errors\x1b[0m");
     }
-    let mut metrics_rw = metrics.write().await;
-    metrics_rw.components.db_pruner.running = Some(false);
-    metrics_rw.components.db_pruner.completed_time = Some(now());
-    metrics_rw.components.db_pruner.completed_successfully = Some(step_errors == 0);
+    let completed_time = now();
+    let results = {
+        let mut metrics_rw = metrics.write().await;
+        metrics_rw.components.db_pruner.running = Some(false);
+        metrics_rw.components.db_pruner.completed_time = Some(completed_time);
+        metrics_rw.components.db_pruner.completed_successfully = Some(step_errors == 0);
+        metrics_rw.components.db_pruner.results.clone().unwrap_or_default()
+    };
+    if let Some(path) = &pruning_config.prune_report_csv {
+        append_prune_report(path, completed_time, step_errors == 0, dry_run, &results);
+    }
+}
+
+/// Appends one row per pruning run to the CSV report, writing a header first if the file is empty. Failures are only logged
+fn append_prune_report(
+    path: &str,
+    completed_time: DateTime<Utc>,
+    success: bool,
+    dry_run: bool,
+    results: &HashMap<String, MetricsComponentDbPrunerResult>,
+) {
+    let write = || -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut csv = String::new();
+        if file.metadata()?.len() == 0 {
+            let mut header = vec!["completed_time".to_string(), "success".to_string(), "dry_run".to_string()];
+            for step in REPORT_STEPS {
+                let column = step.replace(" (", "_").replace(')', "");
+                header.push(format!("{column}_rows_deleted"));
+                header.push(format!("{column}_duration_ms"));
+            }
+            csv.push_str(&header.join(","));
+            csv.push('\n');
+        }
+        let mut row = vec![completed_time.to_rfc3339(), success.to_string(), dry_run.to_string()];
+        for step in REPORT_STEPS {
+            let result = results.get(step);
+            row.push(result.and_then(|r| r.rows_deleted).map(|r| r.to_string()).unwrap_or_default());
+            row.push(result.and_then(|r| r.duration).map(|d| d.as_millis().to_string()).unwrap_or_default());
+        }
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+        file.write_all(csv.as_bytes())
+    };
+    match write() {
+        Ok(_) => info!("Pruning report appended to {path}"),
+        Err(e) => warn!("Writing pruning report {path} FAILED: {e}"),
+    }
+}
+
+/// Runs the steps in order, stopping on shutdown. Returns the number of failed steps
+async fn prune_steps(steps: Vec<PruneStep>, signal_handler: &SignalHandler) -> i32 {
+    let mut step_errors = 0;
+    for step in steps {
+        if signal_handler.is_shutdown() {
+            break;
+        }
+        step_errors += step.await as i32;
+    }
+    step_errors
 }
 
 pub async fn prune_step<F, Fut, E>(
     step_name: &'static str,
     metrics: Arc<RwLock<Metrics>>,
+    dry_run: bool,
     db_call: F,
-    cutoff_blue_score: i64,
+    cutoff_score: i64,
     cutoff_time: DateTime<Utc>,
 ) -> bool
 where
@@ -208,25 +362,29 @@ where
     Fut: Future<Output = Result<u64, E>> + Send + 'static,
     E: Error + Send + Sync + 'static,
 {
-    if cutoff_blue_score > 0 {
-        info!("Pruning {step_name} rows older than {cutoff_time} (bs: {cutoff_blue_score})");
+    if cutoff_score > 0 {
+        info!("Pruning {step_name} rows older than {cutoff_time} (score: {cutoff_score})");
     } else {
         info!("Pruning {step_name} rows older than {cutoff_time}");
     }
     let start_time = now();
     let mut metrics_result =
-        MetricsComponentDbPrunerResult { start_time, cutoff_time, duration: None, success: None, rows_deleted: None };
+        MetricsComponentDbPrunerResult { start_time, cutoff_time, duration: None, success: None, rows_deleted: None, dry_run };
     {
         let mut metrics_rw = metrics.write().await;
         metrics_rw.components.db_pruner.results.as_mut().unwrap().insert(step_name.to_string(), metrics_result.clone());
     }
-    let step_result = db_call((cutoff_blue_score, cutoff_time.timestamp_millis())).await;
+    let step_result = db_call((cutoff_score, cutoff_time.timestamp_millis())).await;
 
     let success = step_result.is_ok();
     metrics_result.success = Some(success);
     metrics_result.duration = Some(now().signed_duration_since(start_time).to_std().unwrap());
 
     match step_result {
+        Ok(rows_affected) if dry_run => {
+            info!("Pruning {step_name} would delete {rows_affected} rows");
+            metrics_result.rows_deleted = Some(rows_affected);
+        }
         Ok(rows_affected) => {
             info!("Pruned {step_name}, {rows_affected} rows deleted");
             metrics_result.rows_deleted = Some(rows_affected);
@@ -0,0 +1,42 @@
+use crate::blocks::fetch_blocks::{BlockData, TransactionData};
+use crate::checkpoint::CheckpointBlock;
+use crate::web::model::metrics::Metrics;
+use crossbeam_queue::ArrayQueue;
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_WINDOW: usize = 60; // Samples kept for the rolling max
+
+/// Periodically records the internal queue lengths (current and rolling max) into the metrics
+pub async fn queue_sampler(
+    signal_handler: SignalHandler,
+    metrics: Arc<RwLock<Metrics>>,
+    blocks_queue: Arc<ArrayQueue<BlockData>>,
+    txs_queue: Arc<ArrayQueue<TransactionData>>,
+    checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
+) {
+    let mut samples: VecDeque<[u64; 3]> = VecDeque::with_capacity(MAX_WINDOW);
+    while !signal_handler.is_shutdown() {
+        let sample = [blocks_queue.len() as u64, txs_queue.len() as u64, checkpoint_queue.len() as u64];
+        if samples.len() == MAX_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+        let max = |i: usize| samples.iter().map(|s| s[i]).max().unwrap_or_default();
+        {
+            let queues = &mut metrics.write().await.queues;
+            queues.blocks = sample[0];
+            queues.transactions = sample[1];
+            queues.checkpoint = sample[2];
+            queues.blocks_max = max(0);
+            queues.transactions_max = max(1);
+            queues.checkpoint_max = max(2);
+        }
+        sleep(SAMPLE_INTERVAL).await;
+    }
+}
@@ -8,8 +8,11 @@ pub struct Settings {
     pub cli_args: CliArgs,
     pub net_bps: u8,
     pub net_tps_max: u16,
+    pub vcp_synced_threshold: u64,
     #[schema(value_type = String)]
     pub block_checkpoint: KaspaHash,
     #[schema(value_type = String)]
     pub vcp_checkpoint: KaspaHash,
+    #[schema(value_type = Option<String>)]
+    pub backfill_to: Option<KaspaHash>,
 }
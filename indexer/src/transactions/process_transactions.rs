@@ -1,21 +1,24 @@
 use crate::blocks::fetch_blocks::TransactionData;
 use crate::checkpoint::{CheckpointBlock, CheckpointOrigin};
+use crate::event_log::EventLog;
 use crate::settings::Settings;
 use crate::web::model::metrics::Metrics;
 use crossbeam_queue::ArrayQueue;
 use futures_util::{StreamExt, stream};
 use indexmap::IndexSet;
 use kaspa_hashes::Hash as KaspaHash;
-use log::{debug, info, trace, warn};
+use log::{debug, error, info, trace, warn};
 use moka::sync::Cache;
-use simply_kaspa_cli::cli_args::{CliDisable, CliField};
+use simply_kaspa_cli::cli_args::{CliDisable, CliEnable, CliField};
 use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::models::address_summary::AddressSeen;
 use simply_kaspa_database::models::address_transaction::AddressTransaction;
 use simply_kaspa_database::models::script_transaction::ScriptTransaction;
 use simply_kaspa_database::models::transaction::Transaction;
 use simply_kaspa_mapping::mapper::KaspaDbMapper;
 use simply_kaspa_signal::signal_handler::SignalHandler;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
@@ -30,14 +33,27 @@ pub async fn process_transactions(
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
     mapper: KaspaDbMapper,
+    event_log: EventLog,
 ) {
-    let ttl = settings.cli_args.cache_ttl;
+    let ttl = settings.cli_args.tx_cache_ttl();
     let cache_size = settings.net_tps_max as u64 * ttl * 2;
     let tx_id_cache: Cache<KaspaHash, ()> = Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(cache_size).build();
+    if settings.cli_args.is_enabled(CliEnable::CachePreload) {
+        let start_time = Instant::now();
+        match database.select_recent_transaction_ids((settings.net_tps_max as u64 * ttl) as i64).await {
+            Ok(transaction_ids) => {
+                let count = transaction_ids.len();
+                for transaction_id in transaction_ids {
+                    tx_id_cache.insert(transaction_id.into(), ());
+                }
+                info!("Preloaded {} transaction ids into the cache in {}ms", count, start_time.elapsed().as_millis());
+            }
+            Err(e) => warn!("Failed to preload the transaction id cache: {e}"),
+        }
+    }
 
-    let batch_scale = settings.cli_args.batch_scale;
-    let batch_concurrency = settings.cli_args.batch_concurrency;
-    let batch_size = (500f64 * batch_scale) as usize;
+    let batch_size = (500f64 * settings.cli_args.batch_scale) as usize;
+    let commit_interval_secs = settings.cli_args.commit_interval_secs;
 
     let disable_transactions = settings.cli_args.is_disabled(CliDisable::TransactionsTable);
     let disable_address_transactions = settings.cli_args.is_disabled(CliDisable::AddressesTransactionsTable);
@@ -45,6 +61,7 @@ pub async fn process_transactions(
     let disable_rejected_non_cb_transactions = settings.cli_args.is_disabled(CliDisable::RejectedNonCbTransactions);
     let exclude_tx_out_script_public_key_address = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress);
     let exclude_tx_out_script_public_key = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKey);
+    let strict_verbose_data = settings.cli_args.strict_verbose_data;
 
     let mut transactions = vec![];
     let mut tx_address_transactions: IndexSet<_> = IndexSet::new();
@@ -73,8 +90,21 @@ pub async fn process_transactions(
                 daa_score: transaction_data.block_daa_score,
                 blue_score: transaction_data.block_blue_score,
             });
-            for transaction in transaction_data.transactions {
-                if mapper.is_self_send_outputs_only(&transaction) {
+            let block_hash = transaction_data.block_hash;
+            for (i, transaction) in transaction_data.transactions.into_iter().enumerate() {
+                if let Err(e) = mapper.check_verbose_data(&transaction) {
+                    if strict_verbose_data {
+                        panic!("Mapping transaction {i} of block {block_hash} FAILED: {e}");
+                    }
+                    // The checkpoint block is still registered, the remaining transactions of the block are kept
+                    error!("Mapping transaction {i} of block {block_hash} FAILED, skipping it: {e}");
+                    metrics.write().await.components.transaction_processor.add_skipped_transaction(format!("{block_hash}:{i}"));
+                    continue;
+                }
+                if mapper.is_self_send_outputs_only(&transaction)
+                    || mapper.is_excluded_subnetwork(&transaction)
+                    || !mapper.is_watched(&transaction)
+                {
                     continue;
                 }
                 if !disable_rejected_transactions && (!disable_rejected_non_cb_transactions || transaction.subnetwork_id.is_builtin())
@@ -99,76 +129,162 @@ pub async fn process_transactions(
             }
 
             if checkpoint_blocks.len() >= batch_size
-                || (!checkpoint_blocks.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() > 1)
+                || (!checkpoint_blocks.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() >= commit_interval_secs)
             {
-                let last_checkpoint = checkpoint_blocks.last().unwrap().clone();
-                let last_block_time = last_checkpoint.timestamp;
-
-                if !disable_rejected_transactions {
-                    loop {
-                        if let Some(vcp) = &metrics.read().await.components.virtual_chain_processor.last_block
-                            && vcp.daa_score.saturating_sub(checkpoint_blocks.last().unwrap().daa_score) >= 3 * settings.net_bps as u64
-                        {
-                            break;
-                        }
-                        debug!("Transaction processor is waiting for virtual chain processor to catch up...");
-                        sleep(Duration::from_millis(1000)).await;
-                        if signal_handler.is_shutdown() {
-                            return;
-                        }
-                    }
-                    let start_commit_time = Instant::now();
-                    let transactions_len = transactions.len();
-
-                    let tx_handle = task::spawn(insert_txs(batch_scale, batch_concurrency, transactions, false, database.clone()));
-                    let tx_addr_handle = if !exclude_tx_out_script_public_key_address {
-                        task::spawn(insert_tx_addr(
-                            batch_scale,
-                            batch_concurrency,
-                            tx_address_transactions.into_iter().collect(),
-                            database.clone(),
-                        ))
-                    } else {
-                        task::spawn(insert_tx_script(
-                            batch_scale,
-                            batch_concurrency,
-                            tx_script_transactions.into_iter().collect(),
-                            database.clone(),
-                        ))
-                    };
-                    let rows_affected_tx = tx_handle.await.unwrap();
-                    let rows_affected_tx_addr = tx_addr_handle.await.unwrap();
-
-                    let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
-                    let tps = if commit_time > 0 { transactions_len as f64 / commit_time as f64 * 1000f64 } else { 0.0 };
-                    info!(
-                        "Committed {} new txs in {}ms ({:.1} tps, {} adr_tx). Last tx: {}",
-                        rows_affected_tx,
-                        commit_time,
-                        tps,
-                        rows_affected_tx_addr,
-                        chrono::DateTime::from_timestamp_millis(last_block_time as i64 / 1000 * 1000).unwrap()
-                    );
-                    transactions = vec![];
-                    tx_address_transactions = IndexSet::new();
-                    tx_script_transactions = IndexSet::new();
-                }
+                commit_transactions(
+                    &settings,
+                    &signal_handler,
+                    &metrics,
+                    &checkpoint_queue,
+                    &database,
+                    &event_log,
+                    std::mem::take(&mut transactions),
+                    std::mem::take(&mut tx_address_transactions),
+                    std::mem::take(&mut tx_script_transactions),
+                    std::mem::take(&mut checkpoint_blocks),
+                )
+                .await;
+                last_commit_time = Instant::now();
+            }
+        } else {
+            sleep(Duration::from_millis(settings.cli_args.queue_poll_ms)).await;
+        }
+    }
+    if !checkpoint_blocks.is_empty() {
+        info!("Committing transactions of {} remaining blocks before shutdown", checkpoint_blocks.len());
+        commit_transactions(
+            &settings,
+            &signal_handler,
+            &metrics,
+            &checkpoint_queue,
+            &database,
+            &event_log,
+            transactions,
+            tx_address_transactions,
+            tx_script_transactions,
+            checkpoint_blocks,
+        )
+        .await;
+    }
+}
+
+async fn commit_transactions(
+    settings: &Settings,
+    signal_handler: &SignalHandler,
+    metrics: &Arc<RwLock<Metrics>>,
+    checkpoint_queue: &ArrayQueue<CheckpointBlock>,
+    database: &KaspaDbClient,
+    event_log: &EventLog,
+    transactions: Vec<Transaction>,
+    tx_address_transactions: IndexSet<AddressTransaction>,
+    tx_script_transactions: IndexSet<ScriptTransaction>,
+    checkpoint_blocks: Vec<CheckpointBlock>,
+) {
+    let batch_scale_transactions = settings.cli_args.batch_scale_transactions();
+    let batch_scale_addresses = settings.cli_args.batch_scale_addresses();
+    let batch_concurrency_transactions = settings.cli_args.batch_concurrency_transactions();
+    let batch_concurrency_addresses = settings.cli_args.batch_concurrency_addresses();
+    let disable_rejected_transactions = settings.cli_args.is_disabled(CliDisable::RejectedTransactions);
+    let exclude_tx_out_script_public_key_address = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress);
+    let enable_address_summary = settings.cli_args.is_enabled(CliEnable::AddressSummary);
 
-                let mut metrics = metrics.write().await;
-                metrics.components.transaction_processor.update_last_block(last_checkpoint.into());
-                drop(metrics);
+    let last_checkpoint = checkpoint_blocks.last().unwrap().clone();
+    let last_block_time = last_checkpoint.timestamp;
 
-                for checkpoint_block in checkpoint_blocks {
-                    while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
-                        warn!("Checkpoint queue is full");
-                        sleep(Duration::from_secs(1)).await;
-                    }
-                }
-                checkpoint_blocks = vec![];
-                last_commit_time = Instant::now();
+    if !disable_rejected_transactions {
+        // On shutdown (or when backfilling) the remaining transactions are committed without waiting for the vcp
+        while settings.backfill_to.is_none() && !signal_handler.is_shutdown() {
+            if let Some(vcp) = &metrics.read().await.components.virtual_chain_processor.last_block
+                && vcp.daa_score.saturating_sub(last_checkpoint.daa_score) >= 3 * settings.net_bps as u64
+            {
+                break;
             }
+            debug!("Transaction processor is waiting for virtual chain processor to catch up...");
+            sleep(Duration::from_millis(settings.cli_args.queue_full_wait_ms)).await;
+        }
+        let start_commit_time = Instant::now();
+        let transactions_len = transactions.len();
+
+        let tx_handle = task::spawn(timed(insert_txs(
+            batch_scale_transactions,
+            batch_concurrency_transactions,
+            transactions,
+            false,
+            database.clone(),
+        )));
+        let addr_seen_handle = if enable_address_summary {
+            let addresses_seen = map_addresses_seen(&tx_address_transactions);
+            Some(task::spawn(insert_addr_seen(batch_scale_addresses, batch_concurrency_addresses, addresses_seen, database.clone())))
+        } else {
+            None
+        };
+        let tx_addr_handle = if !exclude_tx_out_script_public_key_address {
+            task::spawn(timed(insert_tx_addr(
+                batch_scale_addresses,
+                batch_concurrency_addresses,
+                tx_address_transactions.into_iter().collect(),
+                database.clone(),
+            )))
         } else {
-            sleep(Duration::from_millis(100)).await;
+            task::spawn(timed(insert_tx_script(
+                batch_scale_addresses,
+                batch_concurrency_addresses,
+                tx_script_transactions.into_iter().collect(),
+                database.clone(),
+            )))
+        };
+        let (rows_affected_tx, tx_time) = tx_handle.await.unwrap();
+        let (rows_affected_tx_addr, tx_addr_time) = tx_addr_handle.await.unwrap();
+        if let Some(addr_seen_handle) = addr_seen_handle {
+            addr_seen_handle.await.unwrap();
+        }
+
+        let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
+        let tps = if commit_time > 0 { transactions_len as f64 / commit_time as f64 * 1000f64 } else { 0.0 };
+        info!(
+            "Committed {} new txs in {}ms ({:.1} tps, {} adr_tx). Last tx: {}",
+            rows_affected_tx,
+            commit_time,
+            tps,
+            rows_affected_tx_addr,
+            chrono::DateTime::from_timestamp_millis(last_block_time as i64 / 1000 * 1000).unwrap()
+        );
+        if let Some(slow_commit_warn_ms) = settings.cli_args.slow_commit_warn_ms
+            && commit_time > slow_commit_warn_ms as u128
+        {
+            warn!(
+                "Slow transaction commit: {}ms exceeds {}ms (transactions: {} rows in {}ms, {}: {} rows in {}ms)",
+                commit_time,
+                slow_commit_warn_ms,
+                rows_affected_tx,
+                tx_time,
+                if !exclude_tx_out_script_public_key_address { "addresses_transactions" } else { "scripts_transactions" },
+                rows_affected_tx_addr,
+                tx_addr_time
+            );
+        }
+        event_log.log_commit(
+            "transactions",
+            &[
+                ("transactions", rows_affected_tx),
+                (
+                    if !exclude_tx_out_script_public_key_address { "addresses_transactions" } else { "scripts_transactions" },
+                    rows_affected_tx_addr,
+                ),
+            ],
+            commit_time,
+            &last_checkpoint,
+        );
+    }
+
+    let mut metrics = metrics.write().await;
+    metrics.components.transaction_processor.update_last_block(last_checkpoint.into());
+    drop(metrics);
+
+    for checkpoint_block in checkpoint_blocks {
+        while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
+            warn!("Checkpoint queue is full");
+            sleep(Duration::from_millis(settings.cli_args.queue_full_wait_ms)).await;
         }
     }
 }
@@ -180,13 +296,11 @@ pub async fn insert_txs(
     upsert_inputs: bool,
     database: KaspaDbClient,
 ) -> u64 {
-    let batch_size = min((250f64 * batch_scale) as u16, 7000) as usize;
+    let batch_size = min((250f64 * batch_scale) as u16, 4600) as usize; // 14 cols: 4600*14=64400 < 65535
     let key = "transactions";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
-    let mut values = values;
-    values.sort_by(|a, b| a.transaction_id.cmp(&b.transaction_id));
-    let chunks: Vec<Vec<_>> = values.chunks(batch_size).map(|c| c.to_vec()).collect();
+    let chunks = prepare_txs(values, batch_size);
     let rows_affected = stream::iter(chunks.into_iter().map(|chunk| {
         let db = database.clone();
         async move { db.insert_transactions(&chunk, upsert_inputs).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}")) }
@@ -203,13 +317,48 @@ pub async fn insert_tx_addr(batch_scale: f64, batch_concurrency: i8, values: Vec
     let key = "addresses_transactions";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
+    let chunks = prepare_tx_addr(values, batch_size);
+    let rows_affected = stream::iter(chunks.into_iter().map(|chunk| {
+        let db = database.clone();
+        async move { db.insert_address_transactions(&chunk).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}")) }
+    }))
+    .buffer_unordered(batch_concurrency as usize)
+    .fold(0, |acc, rows| async move { acc + rows })
+    .await;
+    debug!("Committed {} {} in {}ms", rows_affected, key, start_time.elapsed().as_millis());
+    rows_affected
+}
+
+/// Aggregates the first/last block_time per address, for both receiving outputs and spending inputs
+pub fn map_addresses_seen(address_transactions: &IndexSet<AddressTransaction>) -> Vec<AddressSeen> {
+    let mut addresses_seen: HashMap<&String, AddressSeen> = HashMap::new();
+    for at in address_transactions {
+        addresses_seen
+            .entry(&at.address)
+            .and_modify(|a| {
+                a.first_block_time = a.first_block_time.min(at.block_time);
+                a.last_block_time = a.last_block_time.max(at.block_time);
+            })
+            .or_insert_with(|| AddressSeen {
+                address: at.address.clone(),
+                first_block_time: at.block_time,
+                last_block_time: at.block_time,
+            });
+    }
+    addresses_seen.into_values().collect()
+}
+
+pub async fn insert_addr_seen(batch_scale: f64, batch_concurrency: i8, values: Vec<AddressSeen>, database: KaspaDbClient) -> u64 {
+    let batch_size = min((650f64 * batch_scale) as u16, 21000) as usize;
+    let key = "addresses";
+    let start_time = Instant::now();
+    debug!("Processing {} {}", values.len(), key);
     let mut values = values;
-    values
-        .sort_by(|a, b| a.address.cmp(&b.address).then(a.block_time.cmp(&b.block_time)).then(a.transaction_id.cmp(&b.transaction_id)));
+    values.sort_by(|a, b| a.address.cmp(&b.address));
     let chunks: Vec<Vec<_>> = values.chunks(batch_size).map(|c| c.to_vec()).collect();
     let rows_affected = stream::iter(chunks.into_iter().map(|chunk| {
         let db = database.clone();
-        async move { db.insert_address_transactions(&chunk).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}")) }
+        async move { db.upsert_address_seen(&chunk).await.unwrap_or_else(|e| panic!("Upsert {key} FAILED: {e}")) }
     }))
     .buffer_unordered(batch_concurrency as usize)
     .fold(0, |acc, rows| async move { acc + rows })
@@ -246,3 +395,99 @@ pub async fn insert_tx_script(
     debug!("Committed {} {} in {}ms", rows_affected, key, start_time.elapsed().as_millis());
     rows_affected
 }
+
+/// Returns the insert chunks in a reproducible order, independent of the input order
+fn prepare_txs(values: Vec<Transaction>, batch_size: usize) -> Vec<Vec<Transaction>> {
+    let mut values = values;
+    values.sort_by(|a, b| a.transaction_id.cmp(&b.transaction_id));
+    values.chunks(batch_size).map(|c| c.to_vec()).collect()
+}
+
+/// Returns the insert chunks in a reproducible order, independent of the input order
+fn prepare_tx_addr(values: Vec<AddressTransaction>, batch_size: usize) -> Vec<Vec<AddressTransaction>> {
+    let mut values = values;
+    values
+        .sort_by(|a, b| a.address.cmp(&b.address).then(a.block_time.cmp(&b.block_time)).then(a.transaction_id.cmp(&b.transaction_id)));
+    values.chunks(batch_size).map(|c| c.to_vec()).collect()
+}
+
+async fn timed<T>(future: impl Future<Output = T>) -> (T, u128) {
+    let start_time = Instant::now();
+    let result = future.await;
+    (result, start_time.elapsed().as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use kaspa_hashes::Hash as KaspaHash;
+    use simply_kaspa_cli::cli_args::CliArgs;
+
+    fn transaction(i: u8) -> Transaction {
+        Transaction {
+            transaction_id: KaspaHash::from_bytes([i; 32]).into(),
+            subnetwork_id: None,
+            hash: None,
+            mass: None,
+            payload: None,
+            block_time: Some(1000 + i as i64),
+            version: None,
+            inputs: None,
+            outputs: None,
+            block_hash: None,
+            input_count: None,
+            output_count: None,
+            payload_compression: None,
+            fee: None,
+        }
+    }
+
+    fn address_transaction(address: &str, i: u8, block_time: i64) -> AddressTransaction {
+        AddressTransaction { address: address.to_string(), transaction_id: KaspaHash::from_bytes([i; 32]).into(), block_time }
+    }
+
+    /// Collects a batch the way process_transactions does and returns the prepared insert arguments
+    fn prepare_batch(order: &[u8]) -> (Vec<Vec<String>>, Vec<Vec<(String, i64, String)>>) {
+        let mut transactions = vec![];
+        let mut tx_address_transactions = IndexSet::new();
+        for &i in order {
+            transactions.push(transaction(i));
+            tx_address_transactions.insert(address_transaction(&format!("kaspa:{}", i % 3), i, 1000 + i as i64));
+            tx_address_transactions.insert(address_transaction("kaspa:shared", i, 1000 + i as i64));
+        }
+        let txs = prepare_txs(transactions, 4)
+            .into_iter()
+            .map(|chunk| chunk.iter().map(|t| t.transaction_id.to_string()).collect())
+            .collect();
+        let tx_addr = prepare_tx_addr(tx_address_transactions.into_iter().collect(), 4)
+            .into_iter()
+            .map(|chunk| chunk.iter().map(|a| (a.address.clone(), a.block_time, a.transaction_id.to_string())).collect())
+            .collect();
+        (txs, tx_addr)
+    }
+
+    #[test]
+    fn deterministic_inserts_same_batch_same_insert_arguments() {
+        let cli_args = CliArgs::parse_from(["simply-kaspa-indexer", "--deterministic-inserts"]);
+        // Chunks are inserted one at a time, so the chunk order is the insert order
+        assert_eq!(cli_args.batch_concurrency_transactions(), 1);
+        assert_eq!(cli_args.batch_concurrency_addresses(), 1);
+        let expected = prepare_batch(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(prepare_batch(&[7, 2, 9, 0, 5, 3, 8, 1, 6, 4]), expected);
+        assert_eq!(prepare_batch(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 0]), expected);
+        assert_eq!((expected.0.len(), expected.1.len()), (3, 5));
+    }
+
+    #[test]
+    fn map_addresses_seen_spend_updates_last_block_time() {
+        let mut address_transactions = IndexSet::new();
+        address_transactions.insert(address_transaction("kaspa:a", 1, 1000)); // Receiving output
+        address_transactions.insert(address_transaction("kaspa:b", 1, 1000)); // Receiving output
+        address_transactions.insert(address_transaction("kaspa:a", 2, 2000)); // Spending input
+        let mut addresses_seen = map_addresses_seen(&address_transactions);
+        addresses_seen.sort_by(|a, b| a.address.cmp(&b.address));
+        let summary: Vec<_> = addresses_seen.iter().map(|a| (a.address.as_str(), a.first_block_time, a.last_block_time)).collect();
+        assert_eq!(summary, vec![("kaspa:a", 1000, 2000), ("kaspa:b", 1000, 1000)]);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::settings::Settings;
-use crate::transactions::process_transactions::{insert_tx_addr, insert_tx_script, insert_txs};
+use crate::transactions::process_transactions::{insert_addr_seen, insert_tx_addr, insert_tx_script, insert_txs, map_addresses_seen};
 use futures_util::{StreamExt, stream};
 use indexmap::IndexSet;
 use kaspa_hashes::Hash as KaspaHash;
 use kaspa_rpc_core::RpcChainBlockAcceptedTransactions;
 use log::{debug, trace};
 use moka::sync::Cache;
-use simply_kaspa_cli::cli_args::{CliDisable, CliField};
+use simply_kaspa_cli::cli_args::{CliDisable, CliEnable, CliField};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::address_transaction::AddressTransaction;
 use simply_kaspa_database::models::script_transaction::ScriptTransaction;
@@ -19,21 +19,27 @@ use std::time::{Duration, Instant};
 use tokio::task;
 
 pub async fn accept_transactions(
-    batch_scale: f64,
-    batch_concurrency: i8,
     settings: &Settings,
     chain_block_accepted_transactions: &[RpcChainBlockAcceptedTransactions],
     database: &KaspaDbClient,
     mapper: &KaspaDbMapper,
 ) -> (u64, u64, u64) {
-    let ttl = settings.cli_args.cache_ttl;
+    let ttl = settings.cli_args.tx_cache_ttl();
     let cache_size = settings.net_tps_max as u64 * ttl * 2;
     let tx_id_cache: Cache<KaspaHash, ()> = Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(cache_size).build();
 
+    let batch_scale_transactions = settings.cli_args.batch_scale_transactions();
+    let batch_scale_addresses = settings.cli_args.batch_scale_addresses();
+    let batch_scale_acceptances = settings.cli_args.batch_scale_acceptances();
+    let batch_concurrency_transactions = settings.cli_args.batch_concurrency_transactions();
+    let batch_concurrency_addresses = settings.cli_args.batch_concurrency_addresses();
+    let batch_concurrency_acceptances = settings.cli_args.batch_concurrency_acceptances();
+
     let disable_transactions = settings.cli_args.is_disabled(CliDisable::TransactionsTable);
     let disable_address_transactions = settings.cli_args.is_disabled(CliDisable::AddressesTransactionsTable);
     let exclude_tx_out_script_public_key_address = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress);
     let exclude_tx_out_script_public_key = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKey);
+    let enable_address_summary = settings.cli_args.is_enabled(CliEnable::AddressSummary);
 
     let mut accepted_transactions = vec![];
     let mut transactions: Vec<Transaction> = vec![];
@@ -44,12 +50,15 @@ pub async fn accept_transactions(
         let block_hash: SqlHash = chain_block.chain_block_header.hash.unwrap().into();
 
         for transaction in &chain_block.accepted_transactions {
-            if mapper.is_self_send_full(transaction) {
+            if mapper.is_self_send_full(transaction) || !mapper.is_watched_optional(transaction) {
                 continue;
             }
             let transaction_id = transaction.verbose_data.as_ref().unwrap().transaction_id.unwrap();
             accepted_transactions
                 .push(TransactionAcceptance { transaction_id: Some(transaction_id.into()), block_hash: Some(block_hash.clone()) });
+            if mapper.is_excluded_subnetwork_optional(transaction) {
+                continue;
+            }
             if tx_id_cache.contains_key(&transaction_id) {
                 trace!("Known transaction_id {}, skipping", transaction_id);
             } else {
@@ -74,18 +83,43 @@ pub async fn accept_transactions(
         debug!("Received {} accepted transactions ({} to upsert)", accepted_transactions.len(), transactions.len());
     }
 
-    let acceptances_handle =
-        task::spawn(insert_transaction_acceptances(batch_scale, batch_concurrency, accepted_transactions, database.clone()));
-    let tx_handle = task::spawn(insert_txs(batch_scale, batch_concurrency, transactions, true, database.clone()));
+    let acceptances_handle = task::spawn(insert_transaction_acceptances(
+        batch_scale_acceptances,
+        batch_concurrency_acceptances,
+        accepted_transactions,
+        database.clone(),
+    ));
+    let tx_handle =
+        task::spawn(insert_txs(batch_scale_transactions, batch_concurrency_transactions, transactions, true, database.clone()));
+    // Includes spending inputs, so last_block_time also advances when an address spends
+    let addr_seen_handle = if enable_address_summary {
+        let addresses_seen = map_addresses_seen(&address_transactions);
+        Some(task::spawn(insert_addr_seen(batch_scale_addresses, batch_concurrency_addresses, addresses_seen, database.clone())))
+    } else {
+        None
+    };
     let addr_tx_handle = if !exclude_tx_out_script_public_key_address {
-        task::spawn(insert_tx_addr(batch_scale, batch_concurrency, address_transactions.into_iter().collect(), database.clone()))
+        task::spawn(insert_tx_addr(
+            batch_scale_addresses,
+            batch_concurrency_addresses,
+            address_transactions.into_iter().collect(),
+            database.clone(),
+        ))
     } else {
-        task::spawn(insert_tx_script(batch_scale, batch_concurrency, script_transactions.into_iter().collect(), database.clone()))
+        task::spawn(insert_tx_script(
+            batch_scale_addresses,
+            batch_concurrency_addresses,
+            script_transactions.into_iter().collect(),
+            database.clone(),
+        ))
     };
 
     let rows_affected_tx_acc = acceptances_handle.await.unwrap();
     let rows_affected_tx = tx_handle.await.unwrap();
     let rows_affected_tx_addr = addr_tx_handle.await.unwrap();
+    if let Some(addr_seen_handle) = addr_seen_handle {
+        addr_seen_handle.await.unwrap();
+    }
 
     (rows_affected_tx_acc, rows_affected_tx, rows_affected_tx_addr)
 }
@@ -0,0 +1,76 @@
+use crate::settings::Settings;
+use crate::virtual_chain::accept_transactions::accept_transactions;
+use deadpool::managed::{Object, Pool};
+use kaspa_hashes::Hash as KaspaHash;
+use kaspa_rpc_core::RpcDataVerbosityLevel;
+use log::{error, info, warn};
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_kaspad::manager::KaspadManager;
+use simply_kaspa_mapping::mapper::KaspaDbMapper;
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+/// Backfills transactions_acceptances for the chain blocks after `from` up to and including `to`.
+/// Runs independently of the live VCP, no checkpoints are read or saved. Existing acceptances are left untouched.
+pub async fn backfill_acceptances(
+    settings: Settings,
+    signal_handler: SignalHandler,
+    kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
+    database: KaspaDbClient,
+    mapper: KaspaDbMapper,
+    from: KaspaHash,
+    to: KaspaHash,
+) -> Result<(), String> {
+    let err_delay = Duration::from_secs(5);
+    let start_time = Instant::now();
+    let (mut chain_blocks, mut rows_acc_total, mut rows_tx_total) = (0, 0, 0);
+    let mut start_hash = from;
+
+    info!("Backfilling transaction acceptances from chain block {} to {}", from, to);
+    while !signal_handler.is_shutdown() {
+        let kaspad = match kaspad_pool.get().await {
+            Ok(k) => k,
+            Err(e) => {
+                error!("Failed getting kaspad connection from pool: {}", e);
+                sleep(err_delay).await;
+                continue;
+            }
+        };
+        let mut res = match kaspad.get_virtual_chain_from_block_v2(start_hash, Some(RpcDataVerbosityLevel::Full), None).await {
+            Ok(res) => res,
+            Err(e) => {
+                error!("Failed getting virtual chain from start_hash {}: {}", start_hash, e);
+                sleep(err_delay).await;
+                continue;
+            }
+        };
+        if !res.removed_chain_block_hashes.is_empty() {
+            return Err(format!("Block {start_hash} is not in the selected chain"));
+        }
+        if res.added_chain_block_hashes.is_empty() {
+            return Err(format!("Reached the virtual chain tip without finding block {to}"));
+        }
+        let to_index = res.added_chain_block_hashes.iter().position(|h| *h == to);
+        if let Some(i) = to_index {
+            res.chain_block_accepted_transactions.truncate(i + 1);
+        }
+        let (rows_acc, rows_tx, _) = accept_transactions(&settings, &res.chain_block_accepted_transactions, &database, &mapper).await;
+        chain_blocks += res.chain_block_accepted_transactions.len();
+        rows_acc_total += rows_acc;
+        rows_tx_total += rows_tx;
+        info!("Backfilled {} chain blocks, {} acceptances added ({} txs upserted)", chain_blocks, rows_acc_total, rows_tx_total);
+        if to_index.is_some() {
+            info!(
+                "\x1b[32mAcceptance backfill completed in {}s: {} chain blocks, {} acceptances added\x1b[0m",
+                start_time.elapsed().as_secs(),
+                chain_blocks,
+                rows_acc_total
+            );
+            return Ok(());
+        }
+        start_hash = *res.added_chain_block_hashes.last().unwrap();
+    }
+    warn!("Acceptance backfill interrupted at chain block {}", start_hash);
+    Ok(())
+}
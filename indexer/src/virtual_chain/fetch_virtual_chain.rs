@@ -1,7 +1,6 @@
 use crate::settings::Settings;
 use crate::web::model::metrics::Metrics;
 use deadpool::managed::{Object, Pool};
-use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_rpc_core::{GetVirtualChainFromBlockV2Response, RpcDataVerbosityLevel};
 use log::{debug, error};
 use mpsc::Sender;
@@ -22,6 +21,7 @@ pub async fn fetch_virtual_chain(
 ) {
     let poll_interval = Duration::from_millis(settings.cli_args.vcp_interval);
     let err_delay = Duration::from_secs(5);
+    let synced_threshold = settings.vcp_synced_threshold as usize;
 
     let mut start_hash = settings.vcp_checkpoint;
 
@@ -31,7 +31,13 @@ pub async fn fetch_virtual_chain(
         }
 
         debug!("Getting virtual chain from start_hash {}", start_hash);
-        let tip_distance = metrics.read().await.components.virtual_chain_processor.tip_distance.unwrap_or(10);
+        let tip_distance = metrics
+            .read()
+            .await
+            .components
+            .virtual_chain_processor
+            .tip_distance
+            .unwrap_or(settings.cli_args.vcp_initial_tip_distance());
         let kaspad = match kaspad_pool.get().await {
             Ok(k) => k,
             Err(e) => {
@@ -68,7 +74,7 @@ pub async fn fetch_virtual_chain(
                     }
                 }
 
-                if added_blocks_count < 200 {
+                if added_blocks_count < synced_threshold {
                     sleep(poll_interval).await;
                 }
             }
@@ -0,0 +1,66 @@
+use kaspa_hashes::Hash as KaspaHash;
+use kaspa_rpc_core::{RpcDataVerbosityLevel, RpcError};
+use log::{debug, warn};
+use simply_kaspa_kaspad::manager::KaspadClient;
+
+/// Returns the last chain block with timestamp at or before timestamp (ms), starting the search at start_hash (pruning point).
+/// Walks the virtual chain in pages and binary searches the page containing the timestamp by fetching block headers.
+pub async fn find_chain_block_by_timestamp(
+    kaspad: &KaspadClient,
+    start_hash: KaspaHash,
+    timestamp: u64,
+) -> Result<KaspaHash, RpcError> {
+    let block_timestamp =
+        async |hash: KaspaHash| -> Result<u64, RpcError> { Ok(kaspad.get_block(hash, false).await?.header.timestamp) };
+
+    if block_timestamp(start_hash).await? >= timestamp {
+        warn!("Start timestamp is before the pruning point, starting from pruning point {}", start_hash);
+        return Ok(start_hash);
+    }
+    let mut low_hash = start_hash;
+    loop {
+        let response = kaspad.get_virtual_chain_from_block_v2(low_hash, Some(RpcDataVerbosityLevel::None), None).await?;
+        let hashes = response.added_chain_block_hashes;
+        let Some(&last_hash) = hashes.last() else {
+            warn!("Start timestamp is after the virtual chain tip, starting from chain block {}", low_hash);
+            return Ok(low_hash);
+        };
+        if block_timestamp(last_hash).await? <= timestamp {
+            debug!("Chain block {} is before the start timestamp, continuing", last_hash);
+            low_hash = last_hash;
+            continue;
+        }
+        // Find the first chain block after timestamp, the result is the one preceding it
+        let (mut low, mut high) = (0usize, hashes.len() - 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if block_timestamp(hashes[mid]).await? <= timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        return Ok(if low == 0 { low_hash } else { hashes[low - 1] });
+    }
+}
+
+/// Walks back count chain blocks from start_hash by following selected parents, stopping at the pruning point.
+/// Returns the resulting hash and the number of chain blocks actually rewound.
+pub async fn rewind_chain_blocks(
+    kaspad: &KaspadClient,
+    start_hash: KaspaHash,
+    count: u64,
+    pruning_point: KaspaHash,
+) -> Result<(KaspaHash, u64), RpcError> {
+    let mut hash = start_hash;
+    for rewound in 0..count {
+        if hash == pruning_point {
+            warn!("Lookback reached the pruning point {} after {} of {} chain blocks", pruning_point, rewound, count);
+            return Ok((hash, rewound));
+        }
+        let block = kaspad.get_block(hash, false).await?;
+        let verbose_data = block.verbose_data.ok_or_else(|| RpcError::General(format!("Block {hash} is missing verbose data")))?;
+        hash = verbose_data.selected_parent_hash;
+    }
+    Ok((hash, count))
+}
@@ -1,5 +1,7 @@
 mod accept_transactions;
 mod add_chain_blocks;
+pub mod backfill_acceptances;
 pub mod fetch_virtual_chain;
+pub mod find_chain_block;
 pub mod process_virtual_chain;
 mod remove_chain_blocks;
@@ -28,10 +28,12 @@ pub async fn process_virtual_chain(
     mut receiver: Receiver<GetVirtualChainFromBlockV2Response>,
 ) {
     let batch_scale = settings.cli_args.batch_scale;
-    let batch_concurrency = settings.cli_args.batch_concurrency;
     let disable_transaction_acceptance = settings.cli_args.is_disabled(CliDisable::TransactionAcceptance);
+    let synced_threshold = settings.vcp_synced_threshold as usize;
 
-    let mut tip_distance: u64 = 10;
+    let min_tip_distance = settings.cli_args.vcp_min_tip_distance;
+    let max_tip_distance = settings.cli_args.vcp_max_tip_distance;
+    let mut tip_distance: u64 = settings.cli_args.vcp_initial_tip_distance();
     let mut tip_distance_timestamp: u128 = 0;
     let mut tip_distance_history: VecDeque<bool> = VecDeque::new();
     let tip_distance_window = (settings.cli_args.vcp_window * 1_000 / settings.cli_args.vcp_interval).max(1) as usize;
@@ -59,14 +61,14 @@ pub async fn process_virtual_chain(
         }
         tip_distance_history.push_front(has_reorg);
         let reorgs_count = tip_distance_history.iter().filter(|&&x| x).count();
-        if reorgs_count >= 3 {
+        if reorgs_count >= 3 && tip_distance < max_tip_distance {
             tip_distance += 1;
             tip_distance_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
             // Increase distance if new reorgs occur within the window:
             tip_distance_history.pop_front();
             tip_distance_history.push_front(false);
             debug!("Increased vcp tip distance to {tip_distance}");
-        } else if added_blocks_count < 200 && reorgs_count == 0 && tip_distance > 0 {
+        } else if added_blocks_count < synced_threshold && reorgs_count == 0 && tip_distance > min_tip_distance {
             tip_distance -= 1;
             tip_distance_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
             if tip_distance_history.len() == tip_distance_window {
@@ -78,15 +80,8 @@ pub async fn process_virtual_chain(
         }
 
         if !disable_transaction_acceptance {
-            let (rows_affected_tx_acc, rows_affected_tx, rows_affected_tx_addr) = accept_transactions(
-                batch_scale,
-                batch_concurrency,
-                &settings,
-                &res.chain_block_accepted_transactions,
-                &database,
-                &mapper,
-            )
-            .await;
+            let (rows_affected_tx_acc, rows_affected_tx, rows_affected_tx_addr) =
+                accept_transactions(&settings, &res.chain_block_accepted_transactions, &database, &mapper).await;
             let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
             let tps = rows_affected_tx as f64 / commit_time as f64 * 1000f64;
             info!(
@@ -131,7 +126,7 @@ pub async fn process_virtual_chain(
             return;
         }
 
-        if !synced && added_blocks_count < 200 {
+        if !synced && added_blocks_count < synced_threshold {
             let time_to_sync = start_time.elapsed();
             info!(
                 "\x1b[32mVirtual chain processor synced! (in {}:{:0>2}:{:0>2}s)\x1b[0m",
@@ -0,0 +1,24 @@
+use log::{info, warn};
+use simply_kaspa_mapping::mapper::KaspaDbMapper;
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Reloads watch-addresses-file on SIGHUP
+pub async fn watch_addresses_reloader(signal_handler: SignalHandler, mapper: KaspaDbMapper) {
+    let mut shutdown_rx = signal_handler.subscribe();
+    let mut reload_rx = signal_handler.subscribe_reload();
+    while !signal_handler.is_shutdown() {
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            result = reload_rx.recv() => {
+                if let Err(RecvError::Closed) = result {
+                    break;
+                }
+                match mapper.reload_watch_addresses() {
+                    Ok(count) => info!("Watch addresses reloaded: {} addresses", count),
+                    Err(e) => warn!("Failed to reload watch-addresses-file, keeping the current set: {}", e),
+                }
+            }
+        }
+    }
+}
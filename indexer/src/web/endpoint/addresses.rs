@@ -0,0 +1,45 @@
+use crate::web::model::address::{AddressTxCountParams, AddressTxCountResponse};
+use crate::web::model::error::ErrorResponse;
+use crate::web::web_server;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use kaspa_rpc_core::RpcAddress;
+use log::warn;
+use simply_kaspa_database::client::KaspaDbClient;
+
+pub const COUNT_PATH: &str = "/api/addresses/{address}/count";
+
+#[utoipa::path(
+    method(get),
+    path = COUNT_PATH,
+    tag = web_server::ADDRESSES_TAG,
+    description = "Count transactions for address (requires addresses_transactions)",
+    params(
+        ("address" = String, Path, description = "Address, including prefix (kaspa:...)"),
+        AddressTxCountParams,
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Success", body = AddressTxCountResponse, content_type = "application/json"),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid address", body = ErrorResponse, content_type = "application/json"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Failed", body = ErrorResponse, content_type = "application/json")
+    )
+)]
+pub async fn get_address_tx_count(
+    Path(address): Path<String>,
+    Query(params): Query<AddressTxCountParams>,
+    Extension(database_client): Extension<KaspaDbClient>,
+) -> impl IntoResponse {
+    let address = match RpcAddress::try_from(address.as_str()) {
+        Ok(address) => address.payload_to_string(),
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse::from("Address is not a valid kaspa address"))).into_response(),
+    };
+    match database_client.select_address_tx_count(&address, params.since).await {
+        Ok(count) => (StatusCode::OK, Json(AddressTxCountResponse { count })).into_response(),
+        Err(e) => {
+            warn!("Failed to select address tx count {}: {:?}", address, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from("Database query failed"))).into_response()
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::web::model::block::IsChainBlockResponse;
+use crate::web::model::error::ErrorResponse;
+use crate::web::web_server;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use kaspa_hashes::Hash as KaspaHash;
+use log::warn;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use std::str::FromStr;
+
+pub const IS_CHAIN_BLOCK_PATH: &str = "/api/block/{block_hash}/is-chain-block";
+
+#[utoipa::path(
+    method(get),
+    path = IS_CHAIN_BLOCK_PATH,
+    tag = web_server::BLOCKS_TAG,
+    description = "Check if a block is a chain block (requires transaction acceptance)",
+    params(
+        ("block_hash" = String, Path, description = "Block hash (hex)"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Success", body = IsChainBlockResponse, content_type = "application/json"),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid block hash", body = ErrorResponse, content_type = "application/json"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Failed", body = ErrorResponse, content_type = "application/json")
+    )
+)]
+pub async fn get_is_chain_block(
+    Path(block_hash): Path<String>,
+    Extension(database_client): Extension<KaspaDbClient>,
+) -> impl IntoResponse {
+    if block_hash.len() != 64 {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse::from("Block hash must be 64 hex characters"))).into_response();
+    }
+    let block_hash: SqlHash = match KaspaHash::from_str(&block_hash) {
+        Ok(hash) => hash.into(),
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse::from("Block hash is not valid hex"))).into_response(),
+    };
+    match database_client.select_is_chain_block(&block_hash).await {
+        Ok(is_chain_block) => (StatusCode::OK, Json(IsChainBlockResponse { is_chain_block })).into_response(),
+        Err(e) => {
+            warn!("Failed to select is chain block {}: {:?}", block_hash, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from("Database query failed"))).into_response()
+        }
+    }
+}
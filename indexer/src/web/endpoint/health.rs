@@ -1,5 +1,7 @@
 use crate::web::endpoint::metrics::update_metrics;
-use crate::web::model::health::{Health, HealthIndexer, HealthIndexerDetails, HealthIndexerInfo, HealthKaspad, HealthStatus};
+use crate::web::model::health::{
+    Health, HealthIndexer, HealthIndexerDetails, HealthIndexerInfo, HealthKaspad, HealthStatus, HealthSync, HealthSyncComponent,
+};
 use crate::web::model::metrics::{Metrics, MetricsBlock};
 use crate::web::web_server;
 use axum::http::StatusCode;
@@ -7,7 +9,6 @@ use axum::response::IntoResponse;
 use axum::{Extension, Json};
 use chrono::{DateTime, Utc};
 use deadpool::managed::Pool;
-use kaspa_rpc_core::api::rpc::RpcApi;
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_kaspad::manager::KaspadManager;
 use std::sync::Arc;
@@ -16,6 +17,7 @@ use sysinfo::System;
 use tokio::sync::RwLock;
 
 pub const PATH: &str = "/api/health";
+pub const SYNC_PATH: &str = "/api/health/sync";
 
 #[utoipa::path(
     method(get),
@@ -54,6 +56,51 @@ pub async fn get_health(
     (status_code, Json(&health)).into_response()
 }
 
+#[utoipa::path(
+    method(get),
+    path = SYNC_PATH,
+    tag = web_server::INFO_TAG,
+    description = "Get sync status based on the block processor lag, for readiness probes (no kaspad or database queries)",
+    responses(
+        (status = StatusCode::OK, description = "In sync", body = HealthSync, content_type = "application/json"),
+        (status = StatusCode::SERVICE_UNAVAILABLE, description = "Lagging", body = HealthSync, content_type = "application/json")
+    )
+)]
+pub async fn get_health_sync(Extension(metrics): Extension<Arc<RwLock<Metrics>>>) -> impl IntoResponse {
+    let metrics = metrics.read().await;
+    let max_lag_seconds = metrics.settings.as_ref().map(|s| s.cli_args.health_max_lag_secs).unwrap_or(60);
+    let lag_seconds = metrics
+        .components
+        .block_processor
+        .last_block
+        .as_ref()
+        .map(|b| (Utc::now().timestamp_millis() as u64).saturating_sub(b.timestamp) / 1000);
+    let status = match lag_seconds {
+        Some(lag) if lag <= max_lag_seconds => HealthStatus::UP,
+        _ => HealthStatus::DOWN,
+    };
+    let components = &metrics.components;
+    let health_sync = HealthSync {
+        status,
+        lag_seconds,
+        max_lag_seconds,
+        components: vec![
+            HealthSyncComponent { name: "block_fetcher".to_string(), last_block: components.block_fetcher.last_block.clone() },
+            HealthSyncComponent { name: "block_processor".to_string(), last_block: components.block_processor.last_block.clone() },
+            HealthSyncComponent {
+                name: "transaction_processor".to_string(),
+                last_block: components.transaction_processor.last_block.clone(),
+            },
+            HealthSyncComponent {
+                name: "virtual_chain_processor".to_string(),
+                last_block: components.virtual_chain_processor.last_block.clone(),
+            },
+        ],
+    };
+    let status_code = if health_sync.status == HealthStatus::UP { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status_code, Json(health_sync)).into_response()
+}
+
 async fn indexer_health(metrics: Metrics, current_daa: Option<u64>) -> HealthIndexer {
     let mut health = HealthIndexer {
         status: HealthStatus::UP,
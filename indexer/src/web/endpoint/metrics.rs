@@ -57,6 +57,7 @@ pub async fn update_metrics(metrics: Arc<RwLock<Metrics>>, system: Arc<RwLock<Sy
         Ok(database_details) => metrics.database = database_details.into(),
         Err(e) => warn!("Failed to select database details: {:?}", e),
     }
+    metrics.database.write_retries = Some(database_client.write_retries());
     match database_client.select_all_table_details().await {
         Ok(all_table_details) => metrics.database.tables = Some(all_table_details.into_iter().map(|td| td.into()).collect()),
         Err(e) => warn!("Failed to select all table details: {:?}", e),
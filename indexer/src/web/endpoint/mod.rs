@@ -1,2 +1,7 @@
+pub mod addresses;
+pub mod blocks;
 pub mod health;
 pub mod metrics;
+pub mod prometheus;
+pub mod stream;
+pub mod transactions;
@@ -0,0 +1,31 @@
+use crate::web::endpoint::metrics::update_metrics;
+use crate::web::model::metrics::Metrics;
+use crate::web::prometheus::to_prometheus;
+use crate::web::web_server;
+use axum::Extension;
+use axum::http::{HeaderValue, header};
+use axum::response::IntoResponse;
+use simply_kaspa_database::client::KaspaDbClient;
+use std::sync::Arc;
+use sysinfo::System;
+use tokio::sync::RwLock;
+
+pub const PATH: &str = "/metrics";
+
+#[utoipa::path(
+    method(get),
+    path = PATH,
+    tag = web_server::INFO_TAG,
+    description = "Get metrics in Prometheus text exposition format",
+    responses(
+        (status = StatusCode::OK, description = "Success", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_prometheus_metrics(
+    Extension(metrics): Extension<Arc<RwLock<Metrics>>>,
+    Extension(system): Extension<Arc<RwLock<System>>>,
+    Extension(database_client): Extension<KaspaDbClient>,
+) -> impl IntoResponse {
+    let metrics = update_metrics(metrics, system, database_client).await;
+    ([(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"))], to_prometheus(&metrics))
+}
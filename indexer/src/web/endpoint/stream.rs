@@ -0,0 +1,56 @@
+use crate::web::model::stream::StreamBlock;
+use crate::web::web_server;
+use axum::Extension;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use log::debug;
+use simply_kaspa_signal::signal_handler::SignalHandler;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+pub const BLOCKS_PATH: &str = "/api/stream/blocks";
+
+#[utoipa::path(
+    method(get),
+    path = BLOCKS_PATH,
+    tag = web_server::BLOCKS_TAG,
+    description = "WebSocket stream of newly committed blocks (one JSON message per block). Slow clients are disconnected",
+    responses(
+        (status = StatusCode::SWITCHING_PROTOCOLS, description = "Upgraded", body = StreamBlock, content_type = "application/json"),
+    )
+)]
+pub async fn get_blocks_stream(
+    ws: WebSocketUpgrade,
+    Extension(block_stream): Extension<broadcast::Sender<StreamBlock>>,
+    Extension(signal_handler): Extension<SignalHandler>,
+) -> impl IntoResponse {
+    let receiver = block_stream.subscribe();
+    ws.on_upgrade(move |socket| stream_blocks(socket, receiver, signal_handler))
+}
+
+async fn stream_blocks(mut socket: WebSocket, mut receiver: broadcast::Receiver<StreamBlock>, signal_handler: SignalHandler) {
+    let mut shutdown = signal_handler.subscribe();
+    loop {
+        tokio::select! {
+            block = receiver.recv() => match block {
+                Ok(block) => {
+                    let message = serde_json::to_string(&block).expect("Failed to serialize block");
+                    if socket.send(Message::Text(message.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Disconnecting slow block stream client ({skipped} blocks behind)");
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                _ => {}
+            },
+            _ = shutdown.recv() => break,
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
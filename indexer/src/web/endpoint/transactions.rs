@@ -0,0 +1,68 @@
+use crate::web::model::error::ErrorResponse;
+use crate::web::model::transaction::TransactionResponse;
+use crate::web::web_server;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use kaspa_hashes::Hash as KaspaHash;
+use log::warn;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use simply_kaspa_database::models::types::payload::decompress_payload;
+use std::str::FromStr;
+
+pub const PATH: &str = "/api/transactions/{transaction_id}";
+
+#[utoipa::path(
+    method(get),
+    path = PATH,
+    tag = web_server::TRANSACTIONS_TAG,
+    description = "Get transaction by id",
+    params(
+        ("transaction_id" = String, Path, description = "Transaction id (hex)"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Success", body = TransactionResponse, content_type = "application/json"),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid transaction id", body = ErrorResponse, content_type = "application/json"),
+        (status = StatusCode::NOT_FOUND, description = "Not found", body = ErrorResponse, content_type = "application/json"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Failed", body = ErrorResponse, content_type = "application/json")
+    )
+)]
+pub async fn get_transaction(
+    Path(transaction_id): Path<String>,
+    Extension(database_client): Extension<KaspaDbClient>,
+) -> impl IntoResponse {
+    if transaction_id.len() != 64 {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse::from("Transaction id must be 64 hex characters"))).into_response();
+    }
+    let transaction_id: SqlHash = match KaspaHash::from_str(&transaction_id) {
+        Ok(hash) => hash.into(),
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse::from("Transaction id is not valid hex"))).into_response(),
+    };
+    let mut transaction = match database_client.select_transaction(&transaction_id).await {
+        Ok(Some(transaction)) => transaction,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(ErrorResponse::from("Transaction not found"))).into_response(),
+        Err(e) => {
+            warn!("Failed to select transaction {}: {:?}", transaction_id, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from("Database query failed"))).into_response();
+        }
+    };
+    if let Some(payload) = transaction.payload.take() {
+        match decompress_payload(payload, transaction.payload_compression) {
+            Ok(payload) => transaction.payload = Some(payload),
+            Err(e) => {
+                warn!("Failed to decompress payload of transaction {}: {:?}", transaction_id, e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from("Failed to decompress payload"))).into_response();
+            }
+        }
+    }
+    let accepting_block_hash = match database_client.select_transaction_acceptance(&transaction_id).await {
+        Ok(acceptance) => acceptance.and_then(|a| a.block_hash),
+        Err(e) => {
+            warn!("Failed to select transaction acceptance {}: {:?}", transaction_id, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from("Database query failed"))).into_response();
+        }
+    };
+    (StatusCode::OK, Json(TransactionResponse::new(transaction, accepting_block_hash))).into_response()
+}
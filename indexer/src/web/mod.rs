@@ -1,3 +1,4 @@
 pub mod endpoint;
 pub mod model;
+pub mod prometheus;
 pub mod web_server;
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTxCountResponse {
+    pub count: i64,
+}
+
+#[derive(IntoParams, Clone, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct AddressTxCountParams {
+    /// Only count transactions with block_time (epoch millis) greater than or equal to this
+    pub since: Option<i64>,
+}
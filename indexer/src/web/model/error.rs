@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    #[schema(example = "Transaction not found")]
+    pub error: String,
+}
+
+impl From<&str> for ErrorResponse {
+    fn from(error: &str) -> Self {
+        ErrorResponse { error: error.to_string() }
+    }
+}
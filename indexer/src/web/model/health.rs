@@ -1,3 +1,4 @@
+use crate::web::model::metrics::MetricsBlock;
 use kaspa_rpc_core::GetServerInfoResponse;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
@@ -66,6 +67,26 @@ pub struct HealthKaspad {
     pub error: Option<String>,
 }
 
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthSync {
+    pub status: HealthStatus,
+    #[schema(example = "2")]
+    pub lag_seconds: Option<u64>,
+    #[schema(example = "60")]
+    pub max_lag_seconds: u64,
+    pub components: Vec<HealthSyncComponent>,
+}
+
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthSyncComponent {
+    pub name: String,
+    pub last_block: Option<MetricsBlock>,
+}
+
 impl From<(HealthStatus, String)> for HealthKaspad {
     fn from(status_error: (HealthStatus, String)) -> Self {
         let (status, error) = status_error;
@@ -3,9 +3,10 @@ use crate::settings::Settings;
 use bytesize::ByteSize;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
 use simply_kaspa_database::models::query::database_details::DatabaseDetails;
 use simply_kaspa_database::models::query::table_details::TableDetails;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::time::Duration;
 use utoipa::ToSchema;
 
@@ -90,6 +91,19 @@ pub struct MetricsQueues {
     pub transactions: u64,
     #[schema(example = "20000")]
     pub transactions_capacity: u64,
+    #[schema(example = "12")]
+    pub checkpoint: u64,
+    #[schema(example = "30000")]
+    pub checkpoint_capacity: u64,
+    /// Max over the last minute
+    #[schema(example = "311")]
+    pub blocks_max: u64,
+    /// Max over the last minute
+    #[schema(example = "1020")]
+    pub transactions_max: u64,
+    /// Max over the last minute
+    #[schema(example = "40")]
+    pub checkpoint_max: u64,
 }
 
 impl Default for MetricsQueues {
@@ -100,7 +114,17 @@ impl Default for MetricsQueues {
 
 impl MetricsQueues {
     pub fn new() -> Self {
-        Self { blocks: 0, blocks_capacity: 0, transactions: 0, transactions_capacity: 0 }
+        Self {
+            blocks: 0,
+            blocks_capacity: 0,
+            transactions: 0,
+            transactions_capacity: 0,
+            checkpoint: 0,
+            checkpoint_capacity: 0,
+            blocks_max: 0,
+            transactions_max: 0,
+            checkpoint_max: 0,
+        }
     }
 }
 
@@ -132,6 +156,7 @@ pub struct MetricsComponent {
     pub transaction_processor: MetricsComponentTransactionProcessor,
     pub virtual_chain_processor: MetricsComponentVirtualChainProcessor,
     pub db_pruner: MetricsComponentDbPruner,
+    pub continuity_checker: MetricsComponentContinuityChecker,
 }
 
 impl Default for MetricsComponent {
@@ -148,6 +173,7 @@ impl MetricsComponent {
             transaction_processor: MetricsComponentTransactionProcessor::new(),
             virtual_chain_processor: MetricsComponentVirtualChainProcessor::new(),
             db_pruner: MetricsComponentDbPruner::new(),
+            continuity_checker: MetricsComponentContinuityChecker::new(),
         }
     }
 }
@@ -156,6 +182,8 @@ impl MetricsComponent {
 #[serde(rename_all = "camelCase")]
 pub struct MetricsComponentBlockFetcher {
     pub last_block: Option<MetricsBlock>,
+    #[schema(example = "0")]
+    pub reconnects: u64,
 }
 
 impl Default for MetricsComponentBlockFetcher {
@@ -166,7 +194,7 @@ impl Default for MetricsComponentBlockFetcher {
 
 impl MetricsComponentBlockFetcher {
     pub fn new() -> Self {
-        Self { last_block: None }
+        Self { last_block: None, reconnects: 0 }
     }
 
     pub fn update_last_block(&mut self, last_block: MetricsBlock) {
@@ -180,6 +208,11 @@ impl MetricsComponentBlockFetcher {
 #[serde(rename_all = "camelCase")]
 pub struct MetricsComponentBlockProcessor {
     pub last_block: Option<MetricsBlock>,
+    #[schema(example = "0")]
+    pub blocks_committed: u64,
+    #[schema(example = "0")]
+    pub failed_blocks: u64,
+    pub last_failed_blocks: VecDeque<String>,
 }
 
 impl Default for MetricsComponentBlockProcessor {
@@ -189,8 +222,18 @@ impl Default for MetricsComponentBlockProcessor {
 }
 
 impl MetricsComponentBlockProcessor {
+    const LAST_FAILED_BLOCKS_MAX: usize = 100;
+
     pub fn new() -> Self {
-        Self { last_block: None }
+        Self { last_block: None, blocks_committed: 0, failed_blocks: 0, last_failed_blocks: VecDeque::new() }
+    }
+
+    pub fn add_failed_block(&mut self, hash: String) {
+        self.failed_blocks += 1;
+        if self.last_failed_blocks.len() >= Self::LAST_FAILED_BLOCKS_MAX {
+            self.last_failed_blocks.pop_front();
+        }
+        self.last_failed_blocks.push_back(hash);
     }
 
     pub fn update_last_block(&mut self, last_block: MetricsBlock) {
@@ -205,6 +248,10 @@ impl MetricsComponentBlockProcessor {
 pub struct MetricsComponentTransactionProcessor {
     pub enabled: bool,
     pub last_block: Option<MetricsBlock>,
+    #[schema(example = "0")]
+    pub skipped_transactions: u64,
+    /// Entries are block_hash:transaction_index
+    pub last_skipped_transactions: VecDeque<String>,
 }
 
 impl Default for MetricsComponentTransactionProcessor {
@@ -214,8 +261,18 @@ impl Default for MetricsComponentTransactionProcessor {
 }
 
 impl MetricsComponentTransactionProcessor {
+    const LAST_SKIPPED_TRANSACTIONS_MAX: usize = 100;
+
     pub fn new() -> Self {
-        Self { enabled: false, last_block: None }
+        Self { enabled: false, last_block: None, skipped_transactions: 0, last_skipped_transactions: VecDeque::new() }
+    }
+
+    pub fn add_skipped_transaction(&mut self, entry: String) {
+        self.skipped_transactions += 1;
+        if self.last_skipped_transactions.len() >= Self::LAST_SKIPPED_TRANSACTIONS_MAX {
+            self.last_skipped_transactions.pop_front();
+        }
+        self.last_skipped_transactions.push_back(entry);
     }
 
     pub fn update_last_block(&mut self, last_block: MetricsBlock) {
@@ -232,6 +289,10 @@ pub struct MetricsComponentVirtualChainProcessor {
     pub only_blocks: bool,
     #[schema(example = "6")]
     pub tip_distance: Option<u64>,
+    #[schema(example = "0")]
+    pub tip_distance_min: u64,
+    #[schema(example = "1000")]
+    pub tip_distance_max: u64,
     #[schema(example = "1738706345528")]
     pub tip_distance_timestamp: Option<u64>,
     #[schema(example = "2025-04-03T22:47:33.938Z")]
@@ -251,6 +312,8 @@ impl MetricsComponentVirtualChainProcessor {
             enabled: false,
             only_blocks: false,
             tip_distance: None,
+            tip_distance_min: 0,
+            tip_distance_max: 0,
             tip_distance_timestamp: None,
             tip_distance_date_time: None,
             last_block: None,
@@ -307,6 +370,30 @@ pub struct MetricsComponentDbPrunerResult {
     #[serde(with = "humantime_serde")]
     pub duration: Option<Duration>,
     pub rows_deleted: Option<u64>,
+    pub dry_run: bool,
+}
+
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsComponentContinuityChecker {
+    pub enabled: bool,
+    #[schema(example = "100804248")]
+    pub checked_blue_score: Option<u64>,
+    #[schema(example = "0")]
+    pub missing_parents: u64,
+    pub last_missing_parent: Option<String>,
+}
+
+impl Default for MetricsComponentContinuityChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsComponentContinuityChecker {
+    pub fn new() -> Self {
+        Self { enabled: false, checked_blue_score: None, missing_parents: 0, last_missing_parent: None }
+    }
 }
 
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
@@ -330,6 +417,9 @@ pub struct MetricsDb {
     pub max_connections: Option<u64>,
     #[schema(example = "0")]
     pub deadlocks: Option<u64>,
+    /// Writes retried by the indexer after a transient error
+    #[schema(example = "0")]
+    pub write_retries: Option<u64>,
     pub tables: Option<Vec<MetricsDbTable>>,
 }
 
@@ -351,6 +441,7 @@ impl MetricsDb {
             active_connections: None,
             max_connections: None,
             deadlocks: None,
+            write_retries: None,
             tables: None,
         }
     }
@@ -368,6 +459,7 @@ impl From<DatabaseDetails> for MetricsDb {
             active_connections: Some(database_details.active_connections as u64),
             max_connections: Some(database_details.max_connections as u64),
             deadlocks: Some(database_details.deadlocks as u64),
+            write_retries: None,
             tables: None,
         }
     }
@@ -403,6 +495,7 @@ impl From<TableDetails> for MetricsDbTable {
     }
 }
 
+#[serde_as]
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricsBlock {
@@ -412,9 +505,11 @@ pub struct MetricsBlock {
     pub timestamp: u64,
     #[schema(example = "2025-02-04T21:59:05.528Z")]
     pub date_time: DateTime<Utc>,
-    #[schema(example = "102414204")]
+    #[serde_as(as = "DisplayFromStr")]
+    #[schema(value_type = String, example = "102414204")]
     pub daa_score: u64,
-    #[schema(example = "100804248")]
+    #[serde_as(as = "DisplayFromStr")]
+    #[schema(value_type = String, example = "100804248")]
     pub blue_score: u64,
 }
 
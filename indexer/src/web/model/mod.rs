@@ -1,2 +1,7 @@
+pub mod address;
+pub mod block;
+pub mod error;
 pub mod health;
 pub mod metrics;
+pub mod stream;
+pub mod transaction;
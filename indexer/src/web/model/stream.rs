@@ -0,0 +1,31 @@
+use crate::checkpoint::CheckpointBlock;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::ToSchema;
+
+#[serde_as]
+#[derive(ToSchema, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamBlock {
+    #[schema(example = "ee4aa364acf73eda93914eaae61d0e78334d1b8a637ab89ef5e224e1bb9382bd")]
+    pub hash: String,
+    #[schema(example = "1738706345528")]
+    pub timestamp: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    #[schema(value_type = String, example = "102414204")]
+    pub daa_score: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    #[schema(value_type = String, example = "100804248")]
+    pub blue_score: u64,
+}
+
+impl From<CheckpointBlock> for StreamBlock {
+    fn from(checkpoint_block: CheckpointBlock) -> Self {
+        Self {
+            hash: checkpoint_block.hash.to_string(),
+            timestamp: checkpoint_block.timestamp,
+            daa_score: checkpoint_block.daa_score,
+            blue_score: checkpoint_block.blue_score,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Amounts are serialized as strings to avoid precision loss in JSON clients (beyond 2^53)
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as, skip_serializing_none};
+use simply_kaspa_database::models::transaction::Transaction;
+use simply_kaspa_database::models::transaction_input::TransactionInput;
+use simply_kaspa_database::models::transaction_output::TransactionOutput;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use utoipa::ToSchema;
+
+#[serde_as]
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionResponse {
+    #[schema(example = "b9382bdee4aa364acf73eda93914eaae61d0e78334d1b8a637ab89ef5e224e1b")]
+    pub transaction_id: String,
+    #[schema(example = "00")]
+    pub subnetwork_id: Option<String>,
+    pub hash: Option<String>,
+    #[schema(example = "2036")]
+    pub mass: Option<i32>,
+    pub payload: Option<String>,
+    #[schema(example = "1738706345528")]
+    pub block_time: Option<i64>,
+    #[schema(example = "0")]
+    pub version: Option<i16>,
+    pub inputs: Option<Vec<TransactionInputResponse>>,
+    pub outputs: Option<Vec<TransactionOutputResponse>>,
+    pub block_hash: Option<String>,
+    pub input_count: Option<i16>,
+    pub output_count: Option<i16>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schema(value_type = Option<String>, example = "2036")]
+    pub fee: Option<i64>,
+    pub accepting_block_hash: Option<String>,
+}
+
+#[serde_as]
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInputResponse {
+    pub index: i16,
+    pub previous_outpoint_hash: Option<String>,
+    pub previous_outpoint_index: Option<i16>,
+    pub signature_script: Option<String>,
+    pub sig_op_count: Option<i16>,
+    pub previous_outpoint_script: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schema(value_type = Option<String>, example = "100000000")]
+    pub previous_outpoint_amount: Option<i64>,
+    pub compute_budget: Option<i16>,
+    pub covenant_id: Option<String>,
+    pub signature_script_length: Option<i32>,
+}
+
+#[serde_as]
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionOutputResponse {
+    pub index: i16,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schema(value_type = Option<String>, example = "100000000")]
+    pub amount: Option<i64>,
+    pub script_public_key: Option<String>,
+    pub script_public_key_address: Option<String>,
+    pub covenant_authorizing_input: Option<i16>,
+    pub covenant_id: Option<String>,
+}
+
+impl TransactionResponse {
+    pub fn new(transaction: Transaction, accepting_block_hash: Option<SqlHash>) -> Self {
+        TransactionResponse {
+            transaction_id: transaction.transaction_id.to_string(),
+            subnetwork_id: transaction.subnetwork_id.map(hex::encode),
+            hash: transaction.hash.map(|h| h.to_string()),
+            mass: transaction.mass,
+            payload: transaction.payload.map(hex::encode),
+            block_time: transaction.block_time,
+            version: transaction.version,
+            inputs: transaction.inputs.map(|inputs| inputs.into_iter().map(|i| i.into()).collect()),
+            outputs: transaction.outputs.map(|outputs| outputs.into_iter().map(|o| o.into()).collect()),
+            block_hash: transaction.block_hash.map(|h| h.to_string()),
+            input_count: transaction.input_count,
+            output_count: transaction.output_count,
+            fee: transaction.fee,
+            accepting_block_hash: accepting_block_hash.map(|h| h.to_string()),
+        }
+    }
+}
+
+impl From<TransactionInput> for TransactionInputResponse {
+    fn from(input: TransactionInput) -> Self {
+        TransactionInputResponse {
+            index: input.index,
+            previous_outpoint_hash: input.previous_outpoint_hash.map(|h| h.to_string()),
+            previous_outpoint_index: input.previous_outpoint_index,
+            signature_script: input.signature_script.map(hex::encode),
+            sig_op_count: input.sig_op_count,
+            previous_outpoint_script: input.previous_outpoint_script.map(hex::encode),
+            previous_outpoint_amount: input.previous_outpoint_amount,
+            compute_budget: input.compute_budget,
+            covenant_id: input.covenant_id.map(|h| h.to_string()),
+            signature_script_length: input.signature_script_length,
+        }
+    }
+}
+
+impl From<TransactionOutput> for TransactionOutputResponse {
+    fn from(output: TransactionOutput) -> Self {
+        TransactionOutputResponse {
+            index: output.index,
+            amount: output.amount,
+            script_public_key: output.script_public_key.map(hex::encode),
+            script_public_key_address: output.script_public_key_address,
+            covenant_authorizing_input: output.covenant_authorizing_input,
+            covenant_id: output.covenant_id.map(|h| h.to_string()),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::web::model::metrics::{Metrics, MetricsBlock};
+use std::fmt::Write;
+
+const PREFIX: &str = "kaspa_indexer";
+
+type Labels<'a> = Vec<(&'a str, String)>;
+
+/// Serializes Metrics to the Prometheus text exposition format. Values which are None are omitted.
+pub fn to_prometheus(metrics: &Metrics) -> String {
+    let mut w = PrometheusWriter { out: String::new() };
+
+    w.gauge("process_cpu_used_percent", "Process cpu usage", vec![(vec![], Some(metrics.process.cpu_used_percent as f64))]);
+    w.gauge("process_memory_used_bytes", "Process memory usage", vec![(vec![], Some(metrics.process.memory_used as f64))]);
+    w.gauge("process_memory_free_bytes", "Free memory", vec![(vec![], Some(metrics.process.memory_free as f64))]);
+    w.gauge("process_uptime_seconds", "Process uptime", vec![(vec![], Some(metrics.process.uptime as f64 / 1000.0))]);
+
+    w.gauge(
+        "queue_size",
+        "Items in internal queue",
+        vec![
+            (vec![("queue", "blocks".to_string())], Some(metrics.queues.blocks as f64)),
+            (vec![("queue", "transactions".to_string())], Some(metrics.queues.transactions as f64)),
+            (vec![("queue", "checkpoint".to_string())], Some(metrics.queues.checkpoint as f64)),
+        ],
+    );
+    w.gauge(
+        "queue_size_max",
+        "Max items in internal queue over the last minute",
+        vec![
+            (vec![("queue", "blocks".to_string())], Some(metrics.queues.blocks_max as f64)),
+            (vec![("queue", "transactions".to_string())], Some(metrics.queues.transactions_max as f64)),
+            (vec![("queue", "checkpoint".to_string())], Some(metrics.queues.checkpoint_max as f64)),
+        ],
+    );
+    w.gauge(
+        "queue_capacity",
+        "Internal queue capacity",
+        vec![
+            (vec![("queue", "blocks".to_string())], Some(metrics.queues.blocks_capacity as f64)),
+            (vec![("queue", "transactions".to_string())], Some(metrics.queues.transactions_capacity as f64)),
+            (vec![("queue", "checkpoint".to_string())], Some(metrics.queues.checkpoint_capacity as f64)),
+        ],
+    );
+
+    let checkpoints = [("block", metrics.block_checkpoint.block.as_ref()), ("vcp", metrics.vcp_checkpoint.block.as_ref())];
+    w.blocks("checkpoint", "checkpoint", &checkpoints);
+
+    let components = &metrics.components;
+    let last_blocks = [
+        ("block_fetcher", components.block_fetcher.last_block.as_ref()),
+        ("block_processor", components.block_processor.last_block.as_ref()),
+        ("transaction_processor", components.transaction_processor.last_block.as_ref()),
+        ("virtual_chain_processor", components.virtual_chain_processor.last_block.as_ref()),
+    ];
+    w.blocks("component_last_block", "component", &last_blocks);
+    w.gauge(
+        "component_enabled",
+        "Component is enabled",
+        vec![
+            (vec![("component", "transaction_processor".to_string())], Some(components.transaction_processor.enabled as u8 as f64)),
+            (
+                vec![("component", "virtual_chain_processor".to_string())],
+                Some(components.virtual_chain_processor.enabled as u8 as f64),
+            ),
+            (vec![("component", "db_pruner".to_string())], Some(components.db_pruner.enabled as u8 as f64)),
+            (vec![("component", "continuity_checker".to_string())], Some(components.continuity_checker.enabled as u8 as f64)),
+        ],
+    );
+    w.gauge(
+        "vcp_tip_distance",
+        "Virtual chain processor tip distance",
+        vec![(vec![], components.virtual_chain_processor.tip_distance.map(|d| d as f64))],
+    );
+    w.gauge(
+        "vcp_tip_distance_bound",
+        "Virtual chain processor tip distance bounds",
+        vec![
+            (vec![("bound", "min".to_string())], Some(components.virtual_chain_processor.tip_distance_min as f64)),
+            (vec![("bound", "max".to_string())], Some(components.virtual_chain_processor.tip_distance_max as f64)),
+        ],
+    );
+
+    let pruner = &components.db_pruner;
+    w.gauge("db_pruner_running", "Database pruner is running", vec![(vec![], pruner.running.map(|r| r as u8 as f64))]);
+    w.gauge(
+        "db_pruner_completed_successfully",
+        "Last database pruning completed successfully",
+        vec![(vec![], pruner.completed_successfully.map(|s| s as u8 as f64))],
+    );
+    w.gauge(
+        "db_pruner_completed_timestamp_seconds",
+        "Last database pruning completion time",
+        vec![(vec![], pruner.completed_time.map(|t| t.timestamp() as f64))],
+    );
+    let mut results: Vec<_> = pruner.results.iter().flatten().collect();
+    results.sort_by(|a, b| a.0.cmp(b.0));
+    w.gauge(
+        "db_pruner_rows_deleted",
+        "Rows deleted by the last database pruning, per step",
+        results.iter().map(|(step, r)| (vec![("step", step.to_string())], r.rows_deleted.map(|r| r as f64))).collect(),
+    );
+    w.gauge(
+        "db_pruner_duration_seconds",
+        "Duration of the last database pruning, per step",
+        results.iter().map(|(step, r)| (vec![("step", step.to_string())], r.duration.map(|d| d.as_secs_f64()))).collect(),
+    );
+    w.gauge(
+        "db_pruner_success",
+        "Result of the last database pruning, per step",
+        results.iter().map(|(step, r)| (vec![("step", step.to_string())], r.success.map(|s| s as u8 as f64))).collect(),
+    );
+
+    w.counter(
+        "block_fetcher_reconnects_total",
+        "Kaspad connections discarded by the block fetcher after a request timeout",
+        vec![(vec![], Some(components.block_fetcher.reconnects as f64))],
+    );
+    w.counter(
+        "blocks_committed_total",
+        "Blocks committed by the block processor",
+        vec![(vec![], Some(components.block_processor.blocks_committed as f64))],
+    );
+    w.counter(
+        "block_processor_failed_blocks_total",
+        "Blocks skipped by the block processor because they failed to map",
+        vec![(vec![], Some(components.block_processor.failed_blocks as f64))],
+    );
+
+    w.counter(
+        "transaction_processor_skipped_transactions_total",
+        "Transactions skipped by the transaction processor because of missing verbose data",
+        vec![(vec![], Some(components.transaction_processor.skipped_transactions as f64))],
+    );
+
+    let continuity_checker = &components.continuity_checker;
+    if continuity_checker.enabled {
+        w.counter(
+            "continuity_missing_parents_total",
+            "Missing block parents found by the continuity checker",
+            vec![(vec![], Some(continuity_checker.missing_parents as f64))],
+        );
+        w.gauge(
+            "continuity_checked_blue_score",
+            "Blue score checked by the continuity checker",
+            vec![(vec![], continuity_checker.checked_blue_score.map(|b| b as f64))],
+        );
+    }
+
+    let database = &metrics.database;
+    w.gauge("database_size_bytes", "Database size", vec![(vec![], database.database_size.map(|v| v as f64))]);
+    w.gauge("database_active_queries", "Active queries", vec![(vec![], database.active_queries.map(|v| v as f64))]);
+    w.gauge("database_blocked_queries", "Blocked queries", vec![(vec![], database.blocked_queries.map(|v| v as f64))]);
+    w.gauge("database_active_connections", "Active connections", vec![(vec![], database.active_connections.map(|v| v as f64))]);
+    w.gauge("database_max_connections", "Max connections", vec![(vec![], database.max_connections.map(|v| v as f64))]);
+    w.counter("database_deadlocks_total", "Deadlocks", vec![(vec![], database.deadlocks.map(|v| v as f64))]);
+    w.counter(
+        "database_write_retries_total",
+        "Writes retried after a transient error",
+        vec![(vec![], database.write_retries.map(|v| v as f64))],
+    );
+    let tables = database.tables.iter().flatten();
+    w.gauge(
+        "database_table_size_bytes",
+        "Total table size (including indexes)",
+        tables.clone().map(|t| (vec![("table", t.name.clone())], Some(t.total_size as f64))).collect(),
+    );
+    w.gauge(
+        "database_table_indexes_size_bytes",
+        "Table indexes size",
+        tables.clone().map(|t| (vec![("table", t.name.clone())], Some(t.indexes_size as f64))).collect(),
+    );
+    w.gauge(
+        "database_table_rows",
+        "Approximate table row count",
+        tables.map(|t| (vec![("table", t.name.clone())], Some(t.approximate_row_count as f64))).collect(),
+    );
+    w.out
+}
+
+struct PrometheusWriter {
+    out: String,
+}
+
+impl PrometheusWriter {
+    fn gauge(&mut self, name: &str, help: &str, samples: Vec<(Labels, Option<f64>)>) {
+        self.metric(name, "gauge", help, samples)
+    }
+
+    fn counter(&mut self, name: &str, help: &str, samples: Vec<(Labels, Option<f64>)>) {
+        self.metric(name, "counter", help, samples)
+    }
+
+    fn blocks(&mut self, name: &str, label: &str, blocks: &[(&str, Option<&MetricsBlock>)]) {
+        let samples = |f: fn(&MetricsBlock) -> f64| {
+            blocks.iter().map(|(value, block)| (vec![(label, value.to_string())], block.map(f))).collect()
+        };
+        self.gauge(&format!("{name}_timestamp_seconds"), "Block timestamp", samples(|b| b.timestamp as f64 / 1000.0));
+        self.gauge(&format!("{name}_daa_score"), "Block DAA score", samples(|b| b.daa_score as f64));
+        self.gauge(&format!("{name}_blue_score"), "Block blue score", samples(|b| b.blue_score as f64));
+    }
+
+    fn metric(&mut self, name: &str, kind: &str, help: &str, samples: Vec<(Labels, Option<f64>)>) {
+        let samples: Vec<_> = samples.into_iter().filter_map(|(labels, value)| value.map(|v| (labels, v))).collect();
+        if samples.is_empty() {
+            return;
+        }
+        let _ = writeln!(self.out, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(self.out, "# TYPE {PREFIX}_{name} {kind}");
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                let _ = writeln!(self.out, "{PREFIX}_{name} {value}");
+            } else {
+                let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{k}=\"{}\"", escape_label(v))).collect();
+                let _ = writeln!(self.out, "{PREFIX}_{name}{{{}}} {value}", labels.join(","));
+            }
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
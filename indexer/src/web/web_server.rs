@@ -1,23 +1,26 @@
 use crate::settings::Settings;
 use crate::web::endpoint;
-use crate::web::endpoint::{health, metrics};
+use crate::web::endpoint::{addresses, blocks, health, metrics, prometheus, stream, transactions};
 use crate::web::model::metrics::Metrics;
+use crate::web::model::stream::StreamBlock;
 use axum::body::{Body, to_bytes};
 use axum::http::{HeaderValue, Request, header};
 use axum::middleware::Next;
 use axum::response::Response;
 use axum::{Extension, Router, middleware, routing::get};
 use deadpool::managed::{Object, Pool};
-use log::{Level, info, trace};
+use log::{Level, info, trace, warn};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_kaspad::manager::KaspadManager;
 use simply_kaspa_signal::signal_handler::SignalHandler;
 use std::io::Error;
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
 use std::sync::Arc;
 use std::vec;
 use sysinfo::System;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 use tower_http::cors::{Any, CorsLayer};
 use utoipa::OpenApi;
 use utoipa::openapi;
@@ -25,6 +28,9 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::{Config, SwaggerUi};
 
 pub const INFO_TAG: &str = "info";
+pub const BLOCKS_TAG: &str = "blocks";
+pub const TRANSACTIONS_TAG: &str = "transactions";
+pub const ADDRESSES_TAG: &str = "addresses";
 
 #[derive(OpenApi)]
 #[openapi(
@@ -34,10 +40,19 @@ pub const INFO_TAG: &str = "info";
     ),
     paths(
         endpoint::health::get_health,
+        endpoint::health::get_health_sync,
         endpoint::metrics::get_metrics,
+        endpoint::prometheus::get_prometheus_metrics,
+        endpoint::blocks::get_is_chain_block,
+        endpoint::stream::get_blocks_stream,
+        endpoint::transactions::get_transaction,
+        endpoint::addresses::get_address_tx_count,
     ),
     tags(
         (name = INFO_TAG, description = "Info API endpoints"),
+        (name = BLOCKS_TAG, description = "Block API endpoints"),
+        (name = TRANSACTIONS_TAG, description = "Transaction API endpoints"),
+        (name = ADDRESSES_TAG, description = "Address API endpoints"),
     ),
 )]
 struct ApiDoc;
@@ -48,6 +63,7 @@ pub struct WebServer {
     metrics: Arc<RwLock<Metrics>>,
     kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
     database_client: KaspaDbClient,
+    block_stream: broadcast::Sender<StreamBlock>,
     system: Arc<RwLock<System>>,
 }
 
@@ -58,8 +74,17 @@ impl WebServer {
         metrics: Arc<RwLock<Metrics>>,
         kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
         database_client: KaspaDbClient,
+        block_stream: broadcast::Sender<StreamBlock>,
     ) -> Self {
-        WebServer { settings, signal, metrics, kaspad_pool, database_client, system: Arc::new(RwLock::new(System::new())) }
+        WebServer {
+            settings,
+            signal,
+            metrics,
+            kaspad_pool,
+            database_client,
+            block_stream,
+            system: Arc::new(RwLock::new(System::new())),
+        }
     }
 
     pub async fn run(self: Arc<Self>) -> Result<(), Error> {
@@ -68,7 +93,13 @@ impl WebServer {
 
         let (api_router, api) = OpenApiRouter::with_openapi(set_server_path(base_path))
             .route(&format!("{}{}", base_path, health::PATH), get(health::get_health))
+            .route(&format!("{}{}", base_path, health::SYNC_PATH), get(health::get_health_sync))
             .route(&format!("{}{}", base_path, metrics::PATH), get(metrics::get_metrics))
+            .route(&format!("{}{}", base_path, prometheus::PATH), get(prometheus::get_prometheus_metrics))
+            .route(&format!("{}{}", base_path, blocks::IS_CHAIN_BLOCK_PATH), get(blocks::get_is_chain_block))
+            .route(&format!("{}{}", base_path, stream::BLOCKS_PATH), get(stream::get_blocks_stream))
+            .route(&format!("{}{}", base_path, transactions::PATH), get(transactions::get_transaction))
+            .route(&format!("{}{}", base_path, addresses::COUNT_PATH), get(addresses::get_address_tx_count))
             .split_for_parts();
         let swagger_config = Config::default().use_base_layout().try_it_out_enabled(true).display_request_duration(true);
         let swagger =
@@ -84,16 +115,43 @@ impl WebServer {
             .layer(Extension(self.kaspad_pool.clone()))
             .layer(Extension(self.database_client.clone()))
             .layer(Extension(self.metrics.clone()))
-            .layer(Extension(self.system.clone()));
+            .layer(Extension(self.system.clone()))
+            .layer(Extension(self.block_stream.clone()))
+            .layer(Extension(self.signal.clone()));
 
+        let mut shutdown_receiver = self.signal.subscribe();
+        let shutdown = async move {
+            let _ = shutdown_receiver.recv().await;
+            info!("Web server shutdown")
+        };
         info!("Starting web server listener on {}, api path: {}/api", listen, base_path);
+        if let Some(socket_path) = listen.strip_prefix("unix:") {
+            #[cfg(unix)]
+            {
+                remove_stale_socket(socket_path);
+                let listener = tokio::net::UnixListener::bind(socket_path).expect("Failed to open listener");
+                let result = axum::serve(listener, app.into_make_service()).with_graceful_shutdown(shutdown).await;
+                remove_stale_socket(socket_path);
+                return result;
+            }
+            #[cfg(not(unix))]
+            panic!("Listen address unix:{socket_path} is not supported, unix sockets are only available on unix platforms");
+        }
         let listener = tokio::net::TcpListener::bind(listen).await.expect("Failed to open listener");
-        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
-            .with_graceful_shutdown(async move {
-                let _ = self.signal.subscribe().recv().await;
-                info!("Web server shutdown")
-            })
-            .await
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown).await
+    }
+}
+
+/// Removes a socket file left behind by a previous run, refuses to remove anything but a socket
+#[cfg(unix)]
+fn remove_stale_socket(socket_path: &str) {
+    if let Ok(metadata) = std::fs::symlink_metadata(socket_path) {
+        if !metadata.file_type().is_socket() {
+            panic!("Listen path {socket_path} exists and is not a socket");
+        }
+        if let Err(e) = std::fs::remove_file(socket_path) {
+            warn!("Failed to remove socket {socket_path}: {e}");
+        }
     }
 }
 
@@ -1,4 +1,5 @@
 use deadpool::managed::{Manager, Metrics, RecycleError, RecycleResult};
+use kaspa_grpc_client::GrpcClient;
 use kaspa_rpc_core::RpcNetworkType;
 use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_wrpc_client::client::ConnectOptions;
@@ -6,21 +7,108 @@ use kaspa_wrpc_client::error::Error;
 use kaspa_wrpc_client::prelude::*;
 use kaspa_wrpc_client::{KaspaRpcClient, WrpcEncoding};
 use log::{debug, info, warn};
-use std::sync::Arc;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+const PNN_MAX_FAILURES: u32 = 3;
+const PNN_RESOLVE_ATTEMPTS: u32 = 5;
+const GRPC_SCHEME: &str = "grpc://";
+
+/// A wRPC or gRPC connection, both are used through the RpcApi trait
+pub enum KaspadClient {
+    Wrpc(KaspaRpcClient),
+    Grpc(GrpcClient),
+}
+
+impl KaspadClient {
+    pub fn is_connected(&self) -> bool {
+        match self {
+            KaspadClient::Wrpc(client) => client.is_connected(),
+            KaspadClient::Grpc(client) => client.is_connected(),
+        }
+    }
+}
+
+impl Deref for KaspadClient {
+    type Target = dyn RpcApi;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            KaspadClient::Wrpc(client) => client,
+            KaspadClient::Grpc(client) => client,
+        }
+    }
+}
+
 pub struct KaspadManager {
     pub network_id: NetworkId,
     pub rpc_url: Option<String>,
+    pnn_url: Mutex<Option<String>>,
+    pnn_failures: AtomicU32,
+}
+
+impl KaspadManager {
+    pub fn new(network_id: NetworkId, rpc_url: Option<String>) -> KaspadManager {
+        KaspadManager { network_id, rpc_url, pnn_url: Mutex::new(None), pnn_failures: AtomicU32::new(0) }
+    }
+
+    /// Returns the configured rpc url, or the currently selected PNN node (resolving one if needed)
+    async fn url(&self) -> Result<String, Error> {
+        if let Some(url) = &self.rpc_url {
+            return Ok(url.clone());
+        }
+        if let Some(url) = self.pnn_url.lock().unwrap().clone() {
+            return Ok(url);
+        }
+        let url = resolve_pnn_url(self.network_id, None).await?;
+        info!("Selected Kaspa PNN node {}", url);
+        *self.pnn_url.lock().unwrap() = Some(url.clone());
+        Ok(url)
+    }
+
+    /// Rotates to a different PNN node after repeated connection failures
+    async fn on_connect_failure(&self, url: &str) {
+        if self.rpc_url.is_some() {
+            return;
+        }
+        let failures = self.pnn_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < PNN_MAX_FAILURES {
+            return;
+        }
+        warn!("Kaspa PNN node {} failed {} consecutive connection attempts, rotating", url, failures);
+        self.pnn_failures.store(0, Ordering::Relaxed);
+        match resolve_pnn_url(self.network_id, Some(url)).await {
+            Ok(new_url) => {
+                info!("Selected Kaspa PNN node {}", new_url);
+                *self.pnn_url.lock().unwrap() = Some(new_url);
+            }
+            Err(e) => {
+                warn!("Failed to resolve a Kaspa PNN node: {e}");
+                *self.pnn_url.lock().unwrap() = None;
+            }
+        }
+    }
 }
 
 impl Manager for KaspadManager {
-    type Type = Arc<KaspaRpcClient>;
+    type Type = Arc<KaspadClient>;
     type Error = Error;
 
     async fn create(&self) -> Result<Self::Type, Self::Error> {
         debug!("Creating connection");
-        Ok(Arc::new(connect_client(self.network_id, self.rpc_url.clone()).await?))
+        let url = self.url().await?;
+        match connect_client(self.network_id, &url).await {
+            Ok(client) => {
+                self.pnn_failures.store(0, Ordering::Relaxed);
+                Ok(Arc::new(client))
+            }
+            Err(e) => {
+                self.on_connect_failure(&url).await;
+                Err(e)
+            }
+        }
     }
 
     async fn recycle(&self, conn: &mut Self::Type, _: &Metrics) -> RecycleResult<Self::Error> {
@@ -35,15 +123,36 @@ impl Manager for KaspadManager {
     }
 }
 
-pub async fn connect_client(network_id: NetworkId, rpc_url: Option<String>) -> Result<KaspaRpcClient, Error> {
-    let url = if let Some(url) = &rpc_url { url } else { &Resolver::default().get_url(WrpcEncoding::Borsh, network_id).await? };
+/// Resolves a PNN node url, preferring one different from `exclude_url`
+async fn resolve_pnn_url(network_id: NetworkId, exclude_url: Option<&str>) -> Result<String, Error> {
+    let resolver = Resolver::default();
+    let mut url = resolver.get_url(WrpcEncoding::Borsh, network_id).await?;
+    for _ in 1..PNN_RESOLVE_ATTEMPTS {
+        if exclude_url != Some(url.as_str()) {
+            break;
+        }
+        url = resolver.get_url(WrpcEncoding::Borsh, network_id).await?;
+    }
+    Ok(url)
+}
 
+/// Connects using gRPC for 'grpc://host:port' urls, otherwise wRPC (Borsh)
+pub async fn connect_client(network_id: NetworkId, url: &str) -> Result<KaspadClient, Error> {
     debug!("Connecting to Kaspad {}", url);
-    let client = KaspaRpcClient::new_with_args(WrpcEncoding::Borsh, Some(url), None, Some(network_id), None)?;
-    client.connect(Some(connect_options())).await.map_err(|e| {
-        warn!("Kaspad connection failed: {e}");
-        e
-    })?;
+    let client = if url.starts_with(GRPC_SCHEME) {
+        let client = GrpcClient::connect(url.to_string()).await.map_err(|e| {
+            warn!("Kaspad connection failed: {e}");
+            Error::Custom(e.to_string())
+        })?;
+        KaspadClient::Grpc(client)
+    } else {
+        let client = KaspaRpcClient::new_with_args(WrpcEncoding::Borsh, Some(url), None, Some(network_id), None)?;
+        client.connect(Some(connect_options())).await.map_err(|e| {
+            warn!("Kaspad connection failed: {e}");
+            e
+        })?;
+        KaspadClient::Wrpc(client)
+    };
 
     let server_info = client.get_server_info().await?;
     let connected_network = format!(
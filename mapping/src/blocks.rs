@@ -1,4 +1,4 @@
-use kaspa_rpc_core::RpcBlock;
+use kaspa_rpc_core::{RpcBlock, RpcHeader};
 
 use simply_kaspa_database::models::block::Block as SqlBlock;
 use simply_kaspa_database::models::block_parent::BlockParent as SqlBlockParent;
@@ -9,6 +9,7 @@ pub fn map_block(
     include_transaction_ids: bool,
     include_merge_set_blues_hashes: bool,
     include_merge_set_reds_hashes: bool,
+    include_merge_set_counts: bool,
     include_selected_parent_hash: bool,
     include_bits: bool,
     include_blue_work: bool,
@@ -20,9 +21,10 @@ pub fn map_block(
     include_timestamp: bool,
     include_utxo_commitment: bool,
     include_version: bool,
-) -> SqlBlock {
-    let verbose_data = block.verbose_data.as_ref().expect("Block verbose_data is missing");
-    SqlBlock {
+    include_raw_header: bool,
+) -> Result<SqlBlock, String> {
+    let verbose_data = block.verbose_data.as_ref().ok_or("Block verbose_data is missing")?;
+    Ok(SqlBlock {
         hash: block.header.hash.into(),
         accepted_id_merkle_root: include_accepted_id_merkle_root.then_some(block.header.accepted_id_merkle_root.into()),
         transaction_ids: (include_transaction_ids && !verbose_data.transaction_ids.is_empty())
@@ -42,7 +44,34 @@ pub fn map_block(
         timestamp: include_timestamp.then_some(block.header.timestamp as i64),
         utxo_commitment: include_utxo_commitment.then_some(block.header.utxo_commitment.into()),
         version: include_version.then_some(block.header.version as i16),
+        merge_set_blues_count: include_merge_set_counts.then_some(verbose_data.merge_set_blues_hashes.len() as i32),
+        merge_set_reds_count: include_merge_set_counts.then_some(verbose_data.merge_set_reds_hashes.len() as i32),
+        raw_header: include_raw_header.then(|| map_raw_header(&block.header)),
+    })
+}
+
+/// Serializes the header as the block hash preimage, the keyed ("BlockHash") blake2b-256 of it equals the block hash
+fn map_raw_header(header: &RpcHeader) -> Vec<u8> {
+    let mut raw = Vec::new();
+    raw.extend(header.version.to_le_bytes());
+    raw.extend((header.parents_by_level.len() as u64).to_le_bytes());
+    for level in header.parents_by_level.iter() {
+        raw.extend((level.len() as u64).to_le_bytes());
+        level.iter().for_each(|parent| raw.extend(parent.as_bytes()));
     }
+    raw.extend(header.hash_merkle_root.as_bytes());
+    raw.extend(header.accepted_id_merkle_root.as_bytes());
+    raw.extend(header.utxo_commitment.as_bytes());
+    raw.extend(header.timestamp.to_le_bytes());
+    raw.extend(header.bits.to_le_bytes());
+    raw.extend(header.nonce.to_le_bytes());
+    raw.extend(header.daa_score.to_le_bytes());
+    raw.extend(header.blue_score.to_le_bytes());
+    let blue_work = header.blue_work.to_be_bytes_var();
+    raw.extend((blue_work.len() as u64).to_le_bytes());
+    raw.extend(blue_work);
+    raw.extend(header.pruning_point.as_bytes());
+    raw
 }
 
 pub fn map_block_parents(block: &RpcBlock) -> Vec<SqlBlockParent> {
@@ -1,12 +1,16 @@
-use kaspa_rpc_core::{RpcBlock, RpcOptionalTransaction, RpcTransaction};
-use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliField};
+use kaspa_rpc_core::{RpcBlock, RpcOptionalTransaction, RpcSubnetworkId, RpcTransaction};
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable, CliField};
 use simply_kaspa_database::models::address_transaction::AddressTransaction as SqlAddressTransaction;
 use simply_kaspa_database::models::block::Block as SqlBlock;
 use simply_kaspa_database::models::block_parent::BlockParent as SqlBlockParent;
 use simply_kaspa_database::models::script_transaction::ScriptTransaction as SqlScriptTransaction;
 use simply_kaspa_database::models::transaction::Transaction as SqlTransaction;
 use std::collections::HashSet;
+use std::fs;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 
+use crate::transactions::PayloadMapping;
 use crate::{blocks, transactions};
 
 #[derive(Clone)]
@@ -15,6 +19,7 @@ pub struct KaspaDbMapper {
     block_transaction_ids: bool,
     block_merge_set_blues_hashes: bool,
     block_merge_set_reds_hashes: bool,
+    block_merge_set_counts: bool,
     block_selected_parent_hash: bool,
     block_bits: bool,
     block_blue_work: bool,
@@ -26,15 +31,19 @@ pub struct KaspaDbMapper {
     block_timestamp: bool,
     block_utxo_commitment: bool,
     block_version: bool,
+    block_raw_header: bool,
     tx_subnetwork_id: bool,
     tx_hash: bool,
     tx_mass: bool,
-    tx_payload: bool,
+    tx_payload: PayloadMapping,
     tx_block_time: bool,
     tx_block_hash: bool,
+    tx_in_out_counts: bool,
+    tx_fee: bool,
     tx_in: bool,
     tx_in_previous_outpoint: bool,
-    tx_in_signature_script: bool,
+    /// None = excluded, Some(0) = full script
+    tx_in_signature_script: Option<usize>,
     tx_in_sig_op_count: bool,
     tx_in_compute_budget: bool,
     tx_in_covenant_id: bool,
@@ -45,6 +54,10 @@ pub struct KaspaDbMapper {
     tx_out_covenant_authorizing_input: bool,
     tx_out_covenant_id: bool,
     ignore_self_sends_groups: Vec<HashSet<String>>,
+    /// Shared between clones, replaced on reload
+    watch_addresses: Option<Arc<RwLock<HashSet<String>>>>,
+    watch_addresses_file: Option<String>,
+    exclude_subnetworks: HashSet<RpcSubnetworkId>,
 }
 
 impl KaspaDbMapper {
@@ -54,6 +67,7 @@ impl KaspaDbMapper {
             block_transaction_ids: !cli_args.is_excluded(CliField::BlockTransactionIds),
             block_merge_set_blues_hashes: !cli_args.is_excluded(CliField::BlockMergeSetBluesHashes),
             block_merge_set_reds_hashes: !cli_args.is_excluded(CliField::BlockMergeSetRedsHashes),
+            block_merge_set_counts: !cli_args.is_excluded(CliField::BlockMergeSetCounts),
             block_selected_parent_hash: !cli_args.is_excluded(CliField::BlockSelectedParentHash),
             block_bits: !cli_args.is_excluded(CliField::BlockBits),
             block_blue_work: !cli_args.is_excluded(CliField::BlockBlueWork),
@@ -65,15 +79,24 @@ impl KaspaDbMapper {
             block_timestamp: !cli_args.is_excluded(CliField::BlockTimestamp),
             block_utxo_commitment: !cli_args.is_excluded(CliField::BlockUtxoCommitment),
             block_version: !cli_args.is_excluded(CliField::BlockVersion),
+            block_raw_header: cli_args.is_enabled(CliEnable::StoreRawHeader),
             tx_subnetwork_id: !cli_args.is_excluded(CliField::TxSubnetworkId),
             tx_hash: !cli_args.is_excluded(CliField::TxHash),
             tx_mass: !cli_args.is_excluded(CliField::TxMass),
-            tx_payload: !cli_args.is_excluded(CliField::TxPayload),
+            tx_payload: PayloadMapping {
+                include: !cli_args.is_excluded(CliField::TxPayload),
+                include_coinbase: !cli_args.no_payload_for_coinbase,
+                max_bytes: cli_args.max_payload_bytes as usize,
+                compress_min_bytes: cli_args.compress_payloads.then_some(cli_args.compress_payloads_min_bytes as usize),
+            },
             tx_block_time: !cli_args.is_excluded(CliField::TxBlockTime),
             tx_block_hash: !cli_args.is_excluded(CliField::TxBlockHash),
+            tx_in_out_counts: cli_args.is_enabled(CliEnable::TransactionsInOutCounts),
+            tx_fee: cli_args.is_enabled(CliEnable::TransactionFee),
             tx_in: !cli_args.is_disabled(CliDisable::TransactionsInputs),
             tx_in_previous_outpoint: !cli_args.is_excluded(CliField::TxInPreviousOutpoint),
-            tx_in_signature_script: !cli_args.is_excluded(CliField::TxInSignatureScript),
+            tx_in_signature_script: (!cli_args.is_excluded(CliField::TxInSignatureScript))
+                .then_some(cli_args.max_signature_script_bytes as usize),
             tx_in_sig_op_count: !cli_args.is_excluded(CliField::TxInSigOpCount),
             tx_in_compute_budget: !cli_args.is_excluded(CliField::TxInComputeBudget),
             tx_in_covenant_id: !cli_args.is_excluded(CliField::TxInCovenantId),
@@ -90,16 +113,30 @@ impl KaspaDbMapper {
                 .map(|s| s.split(',').filter(|a| !a.is_empty()).map(str::to_owned).collect::<HashSet<String>>())
                 .filter(|g| !g.is_empty())
                 .collect(),
+            watch_addresses: cli_args.watch_addresses_file.as_ref().map(|path| {
+                let addresses = read_watch_addresses(path).unwrap_or_else(|e| panic!("Invalid watch-addresses-file: {e}"));
+                Arc::new(RwLock::new(addresses))
+            }),
+            watch_addresses_file: cli_args.watch_addresses_file.clone(),
+            exclude_subnetworks: cli_args
+                .exclude_subnetwork
+                .unwrap_or_default()
+                .iter()
+                .map(|s| {
+                    RpcSubnetworkId::from_str(s).unwrap_or_else(|_| panic!("Invalid exclude-subnetwork '{s}', expected 40 hex chars"))
+                })
+                .collect(),
         }
     }
 
-    pub fn map_block(&self, block: &RpcBlock) -> SqlBlock {
+    pub fn map_block(&self, block: &RpcBlock) -> Result<SqlBlock, String> {
         blocks::map_block(
             block,
             self.block_accepted_id_merkle_root,
             self.block_transaction_ids,
             self.block_merge_set_blues_hashes,
             self.block_merge_set_reds_hashes,
+            self.block_merge_set_counts,
             self.block_selected_parent_hash,
             self.block_bits,
             self.block_blue_work,
@@ -111,6 +148,7 @@ impl KaspaDbMapper {
             self.block_timestamp,
             self.block_utxo_commitment,
             self.block_version,
+            self.block_raw_header,
         )
     }
 
@@ -127,6 +165,7 @@ impl KaspaDbMapper {
             self.tx_payload,
             self.tx_block_time,
             self.tx_block_hash,
+            self.tx_in_out_counts,
             self.tx_in,
             self.tx_in_previous_outpoint,
             self.tx_in_signature_script,
@@ -150,7 +189,7 @@ impl KaspaDbMapper {
     }
 
     pub fn map_optional_transaction(&self, transaction: &RpcOptionalTransaction) -> SqlTransaction {
-        transactions::map_optional_transaction(
+        let mut sql_transaction = transactions::map_optional_transaction(
             transaction,
             self.tx_subnetwork_id,
             self.tx_hash,
@@ -158,6 +197,7 @@ impl KaspaDbMapper {
             self.tx_payload,
             self.tx_block_time,
             self.tx_block_hash,
+            self.tx_in_out_counts,
             self.tx_in,
             self.tx_in_previous_outpoint,
             self.tx_in_signature_script,
@@ -170,7 +210,11 @@ impl KaspaDbMapper {
             self.tx_out_script_public_key_address,
             self.tx_out_covenant_authorizing_input,
             self.tx_out_covenant_id,
-        )
+        );
+        if self.tx_fee {
+            sql_transaction.fee = transactions::map_optional_transaction_fee(transaction);
+        }
+        sql_transaction
     }
 
     pub fn map_optional_transaction_inputs_address(&self, transaction: &RpcOptionalTransaction) -> Vec<SqlAddressTransaction> {
@@ -189,6 +233,10 @@ impl KaspaDbMapper {
         transactions::map_optional_transaction_outputs_script(transaction)
     }
 
+    pub fn check_verbose_data(&self, transaction: &RpcTransaction) -> Result<(), String> {
+        transactions::check_verbose_data(transaction)
+    }
+
     /// Returns `true` if all outputs of this transaction belong to a single ignore-self-sends group.
     /// Used in the rejected-transactions phase where input addresses are unavailable.
     /// Coinbase transactions are never filtered.
@@ -239,4 +287,65 @@ impl KaspaDbMapper {
             .collect();
         self.ignore_self_sends_groups.iter().any(|group| all_addrs.iter().all(|a| group.contains(a)))
     }
+
+    /// Returns `true` if no watch-addresses-file is set, or if any output address is watched.
+    /// Used in the rejected-transactions phase where input addresses are unavailable.
+    pub fn is_watched(&self, transaction: &RpcTransaction) -> bool {
+        let Some(watch_addresses) = &self.watch_addresses else {
+            return true;
+        };
+        let watch_addresses = watch_addresses.read().unwrap();
+        transaction
+            .outputs
+            .iter()
+            .any(|o| watch_addresses.contains(&o.verbose_data.as_ref().unwrap().script_public_key_address.address_to_string()))
+    }
+
+    /// Returns `true` if no watch-addresses-file is set, or if any input or output address is watched.
+    /// Used in the virtual-chain acceptance phase where both sides are available.
+    pub fn is_watched_optional(&self, transaction: &RpcOptionalTransaction) -> bool {
+        let Some(watch_addresses) = &self.watch_addresses else {
+            return true;
+        };
+        let watch_addresses = watch_addresses.read().unwrap();
+        transaction
+            .outputs
+            .iter()
+            .filter_map(|o| o.verbose_data.as_ref().and_then(|v| v.script_public_key_address.as_ref()))
+            .chain(transaction.inputs.iter().filter_map(|i| {
+                i.verbose_data
+                    .as_ref()
+                    .and_then(|v| v.utxo_entry.as_ref())
+                    .and_then(|u| u.verbose_data.as_ref())
+                    .and_then(|v| v.script_public_key_address.as_ref())
+            }))
+            .any(|a| watch_addresses.contains(&a.address_to_string()))
+    }
+
+    /// Re-reads watch-addresses-file, returns the number of watched addresses. The current set is kept on failure
+    pub fn reload_watch_addresses(&self) -> Result<usize, String> {
+        let (Some(watch_addresses), Some(path)) = (&self.watch_addresses, &self.watch_addresses_file) else {
+            return Ok(0);
+        };
+        let addresses = read_watch_addresses(path)?;
+        let count = addresses.len();
+        *watch_addresses.write().unwrap() = addresses;
+        Ok(count)
+    }
+
+    /// Returns `true` if this transaction belongs to an excluded subnetwork
+    pub fn is_excluded_subnetwork(&self, transaction: &RpcTransaction) -> bool {
+        !self.exclude_subnetworks.is_empty() && self.exclude_subnetworks.contains(&transaction.subnetwork_id)
+    }
+
+    /// Returns `true` if this transaction belongs to an excluded subnetwork
+    pub fn is_excluded_subnetwork_optional(&self, transaction: &RpcOptionalTransaction) -> bool {
+        !self.exclude_subnetworks.is_empty() && self.exclude_subnetworks.contains(transaction.subnetwork_id.as_ref().unwrap())
+    }
+}
+
+/// Reads one address per line, empty lines and lines starting with '#' are ignored
+fn read_watch_addresses(path: &str) -> Result<HashSet<String>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    Ok(contents.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).map(str::to_owned).collect())
 }
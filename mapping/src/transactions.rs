@@ -6,6 +6,7 @@ use simply_kaspa_database::models::transaction::Transaction as SqlTransaction;
 use simply_kaspa_database::models::transaction_input::TransactionInput as SqlTransactionInput;
 use simply_kaspa_database::models::transaction_output::TransactionOutput as SqlTransactionOutput;
 use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use simply_kaspa_database::models::types::payload::{PAYLOAD_COMPRESSION_ZSTD, Payload, compress_payload};
 
 /// Compresses a SubnetworkId (20 bytes) to a compact bytea: trailing zeros stripped,
 /// all-zeros (NATIVE) -> None.
@@ -15,17 +16,88 @@ fn compress_subnetwork_id(id: &impl AsRef<[u8; 20]>) -> Option<Vec<u8>> {
     if len == 0 { None } else { Some(bytes[..len].to_vec()) }
 }
 
+#[derive(Clone, Copy)]
+pub struct PayloadMapping {
+    pub include: bool,
+    pub include_coinbase: bool,
+    /// 0 keeps the full payload
+    pub max_bytes: usize,
+    /// None disables compression
+    pub compress_min_bytes: Option<usize>,
+}
+
+/// Returns true for the coinbase subnetwork id (0100..00).
+fn is_coinbase(id: &impl AsRef<[u8; 20]>) -> bool {
+    let bytes = id.as_ref();
+    bytes[0] == 1 && bytes[1..].iter().all(|&b| b == 0)
+}
+
+/// Returns the signature script capped at max_bytes (0 keeps the full script) and its original length if truncated.
+/// None excludes the signature script.
+fn map_signature_script(signature_script: &[u8], max_bytes: Option<usize>) -> (Option<Vec<u8>>, Option<i32>) {
+    match max_bytes {
+        Some(max_bytes) if max_bytes > 0 && signature_script.len() > max_bytes => {
+            (Some(signature_script[..max_bytes].to_vec()), Some(signature_script.len() as i32))
+        }
+        Some(_) => (Some(signature_script.to_vec()), None),
+        None => (None, None),
+    }
+}
+
+/// Returns the payload capped at max_payload_bytes, 0 keeps the full payload.
+fn truncate_payload(payload: &[u8], max_payload_bytes: usize) -> Vec<u8> {
+    if max_payload_bytes > 0 && payload.len() > max_payload_bytes { payload[..max_payload_bytes].to_vec() } else { payload.to_vec() }
+}
+
+/// Returns the (truncated) payload and its compression. Payloads smaller than compress_min_bytes are stored as is,
+/// as are payloads where zstd doesn't reduce the size.
+fn map_payload(payload: &[u8], max_payload_bytes: usize, compress_min_bytes: Option<usize>) -> (Option<Payload>, Option<i16>) {
+    let payload = truncate_payload(payload, max_payload_bytes);
+    match compress_min_bytes {
+        Some(min_bytes) if payload.len() >= min_bytes => match compress_payload(&payload) {
+            Ok(compressed) if compressed.len() < payload.len() => (Some(compressed), Some(PAYLOAD_COMPRESSION_ZSTD)),
+            _ => (Some(payload), None),
+        },
+        _ => (Some(payload), None),
+    }
+}
+
+/// Returns the mapped payload according to payload_mapping, empty and excluded (coinbase) payloads map to None.
+fn map_transaction_payload(
+    payload: &[u8],
+    subnetwork_id: &impl AsRef<[u8; 20]>,
+    payload_mapping: PayloadMapping,
+) -> (Option<Payload>, Option<i16>) {
+    if payload_mapping.include && !payload.is_empty() && (payload_mapping.include_coinbase || !is_coinbase(subnetwork_id)) {
+        map_payload(payload, payload_mapping.max_bytes, payload_mapping.compress_min_bytes)
+    } else {
+        (None, None)
+    }
+}
+
+/// Returns an error if the transaction or any of its outputs lacks the verbose_data required for mapping
+pub fn check_verbose_data(transaction: &RpcTransaction) -> Result<(), String> {
+    if transaction.verbose_data.is_none() {
+        return Err("Transaction verbose_data is missing".to_string());
+    }
+    if let Some(i) = transaction.outputs.iter().position(|o| o.verbose_data.is_none()) {
+        return Err(format!("Transaction output {i} verbose_data is missing"));
+    }
+    Ok(())
+}
+
 pub fn map_transaction(
     transaction: &RpcTransaction,
     include_subnetwork_id: bool,
     include_hash: bool,
     include_mass: bool,
-    include_payload: bool,
+    payload_mapping: PayloadMapping,
     include_block_time: bool,
     include_block_hash: bool,
+    include_in_out_counts: bool,
     include_in: bool,
     include_in_previous_outpoint: bool,
-    include_in_signature_script: bool,
+    in_signature_script: Option<usize>,
     include_in_sig_op_count: bool,
     include_in_compute_budget: bool,
     include_out: bool,
@@ -36,12 +108,13 @@ pub fn map_transaction(
     include_out_covenant_id: bool,
 ) -> SqlTransaction {
     let verbose_data = transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    let (payload, payload_compression) = map_transaction_payload(&transaction.payload, &transaction.subnetwork_id, payload_mapping);
     SqlTransaction {
         transaction_id: verbose_data.transaction_id.into(),
         subnetwork_id: include_subnetwork_id.then(|| compress_subnetwork_id(&transaction.subnetwork_id)).flatten(),
         hash: include_hash.then_some(verbose_data.hash.into()),
         mass: (include_mass && verbose_data.compute_mass != 0).then_some(verbose_data.compute_mass as i32),
-        payload: (include_payload && !transaction.payload.is_empty()).then_some(transaction.payload.to_owned()),
+        payload,
         block_time: include_block_time.then_some(verbose_data.block_time as i64),
         block_hash: include_block_hash.then_some(verbose_data.block_hash.into()),
         version: (transaction.version != 0).then_some(transaction.version as i16),
@@ -50,7 +123,7 @@ pub fn map_transaction(
                 map_transaction_inputs(
                     transaction,
                     include_in_previous_outpoint,
-                    include_in_signature_script,
+                    in_signature_script,
                     include_in_sig_op_count,
                     include_in_compute_budget,
                 )
@@ -68,13 +141,17 @@ pub fn map_transaction(
                 )
             })
             .flatten(),
+        input_count: include_in_out_counts.then_some(transaction.inputs.len() as i16),
+        output_count: include_in_out_counts.then_some(transaction.outputs.len() as i16),
+        payload_compression,
+        fee: None,
     }
 }
 
 pub fn map_transaction_inputs(
     transaction: &RpcTransaction,
     include_previous_outpoint: bool,
-    include_signature_script: bool,
+    signature_script_max_bytes: Option<usize>,
     include_sig_op_count: bool,
     include_compute_budget: bool,
 ) -> Option<Vec<SqlTransactionInput>> {
@@ -83,16 +160,21 @@ pub fn map_transaction_inputs(
             .inputs
             .iter()
             .enumerate()
-            .map(|(i, input)| SqlTransactionInput {
-                index: i as i16,
-                previous_outpoint_hash: include_previous_outpoint.then_some(input.previous_outpoint.transaction_id.into()),
-                previous_outpoint_index: include_previous_outpoint.then_some(input.previous_outpoint.index as i16),
-                signature_script: include_signature_script.then_some(input.signature_script.clone()),
-                sig_op_count: (include_sig_op_count && input.sig_op_count != 0).then_some(input.sig_op_count as i16),
-                previous_outpoint_script: None,
-                previous_outpoint_amount: None,
-                compute_budget: (include_compute_budget && input.compute_budget != 0).then_some(input.compute_budget as i16),
-                covenant_id: None,
+            .map(|(i, input)| {
+                let (signature_script, signature_script_length) =
+                    map_signature_script(&input.signature_script, signature_script_max_bytes);
+                SqlTransactionInput {
+                    index: i as i16,
+                    previous_outpoint_hash: include_previous_outpoint.then_some(input.previous_outpoint.transaction_id.into()),
+                    previous_outpoint_index: include_previous_outpoint.then_some(input.previous_outpoint.index as i16),
+                    signature_script,
+                    sig_op_count: (include_sig_op_count && input.sig_op_count != 0).then_some(input.sig_op_count as i16),
+                    previous_outpoint_script: None,
+                    previous_outpoint_amount: None,
+                    compute_budget: (include_compute_budget && input.compute_budget != 0).then_some(input.compute_budget as i16),
+                    covenant_id: None,
+                    signature_script_length,
+                }
             })
             .collect()
     })
@@ -163,12 +245,13 @@ pub fn map_optional_transaction(
     include_subnetwork_id: bool,
     include_hash: bool,
     include_mass: bool,
-    include_payload: bool,
+    payload_mapping: PayloadMapping,
     include_block_time: bool,
     include_block_hash: bool,
+    include_in_out_counts: bool,
     include_in: bool,
     include_in_previous_outpoint: bool,
-    include_in_signature_script: bool,
+    in_signature_script: Option<usize>,
     include_in_sig_op_count: bool,
     include_in_compute_budget: bool,
     include_in_covenant_id: bool,
@@ -180,13 +263,14 @@ pub fn map_optional_transaction(
     include_out_covenant_id: bool,
 ) -> SqlTransaction {
     let verbose_data = transaction.verbose_data.as_ref().expect("Optional transaction verbose_data is missing");
+    let (payload, payload_compression) =
+        map_transaction_payload(transaction.payload.as_ref().unwrap(), transaction.subnetwork_id.as_ref().unwrap(), payload_mapping);
     SqlTransaction {
         transaction_id: verbose_data.transaction_id.unwrap().into(),
         subnetwork_id: include_subnetwork_id.then(|| compress_subnetwork_id(transaction.subnetwork_id.as_ref().unwrap())).flatten(),
         hash: include_hash.then_some(verbose_data.hash.unwrap().into()),
         mass: (include_mass && verbose_data.compute_mass.unwrap() != 0).then_some(verbose_data.compute_mass.unwrap() as i32),
-        payload: (include_payload && !transaction.payload.as_ref().unwrap().is_empty())
-            .then_some(transaction.payload.as_ref().unwrap().to_owned()),
+        payload,
         block_time: include_block_time.then_some(verbose_data.block_time.unwrap() as i64),
         block_hash: include_block_hash.then_some(verbose_data.block_hash.unwrap().into()),
         version: transaction.version.and_then(|v| (v != 0).then_some(v as i16)),
@@ -195,7 +279,7 @@ pub fn map_optional_transaction(
                 map_optional_transaction_inputs(
                     transaction,
                     include_in_previous_outpoint,
-                    include_in_signature_script,
+                    in_signature_script,
                     include_in_sig_op_count,
                     include_in_compute_budget,
                     include_in_covenant_id,
@@ -214,13 +298,31 @@ pub fn map_optional_transaction(
                 )
             })
             .flatten(),
+        input_count: include_in_out_counts.then_some(transaction.inputs.len() as i16),
+        output_count: include_in_out_counts.then_some(transaction.outputs.len() as i16),
+        payload_compression,
+        fee: None,
+    }
+}
+
+/// Returns the fee (sum of input amounts - sum of output amounts) if all input amounts are resolved, None for coinbase.
+pub fn map_optional_transaction_fee(transaction: &RpcOptionalTransaction) -> Option<i64> {
+    if transaction.inputs.is_empty() {
+        return None;
     }
+    let input_amount = transaction
+        .inputs
+        .iter()
+        .map(|i| i.verbose_data.as_ref().and_then(|v| v.utxo_entry.as_ref()).and_then(|u| u.amount))
+        .sum::<Option<u64>>()?;
+    let output_amount = transaction.outputs.iter().map(|o| o.value).sum::<Option<u64>>()?;
+    input_amount.checked_sub(output_amount).map(|fee| fee as i64)
 }
 
 fn map_optional_transaction_inputs(
     transaction: &RpcOptionalTransaction,
     include_previous_outpoint: bool,
-    include_signature_script: bool,
+    signature_script_max_bytes: Option<usize>,
     include_sig_op_count: bool,
     include_compute_budget: bool,
     include_covenant_id: bool,
@@ -233,11 +335,13 @@ fn map_optional_transaction_inputs(
             .map(|(i, input)| {
                 let utxo = input.verbose_data.as_ref().unwrap().utxo_entry.as_ref().unwrap();
                 let outpoint = input.previous_outpoint.as_ref().unwrap();
+                let (signature_script, signature_script_length) =
+                    map_signature_script(input.signature_script.as_ref().unwrap(), signature_script_max_bytes);
                 SqlTransactionInput {
                     index: i as i16,
                     previous_outpoint_hash: include_previous_outpoint.then(|| outpoint.transaction_id.unwrap().into()),
                     previous_outpoint_index: include_previous_outpoint.then(|| outpoint.index.unwrap() as i16),
-                    signature_script: include_signature_script.then(|| input.signature_script.clone().unwrap()),
+                    signature_script,
                     sig_op_count: (include_sig_op_count && input.sig_op_count != Some(0)).then(|| input.sig_op_count.unwrap() as i16),
                     previous_outpoint_script: include_previous_outpoint
                         .then(|| utxo.script_public_key.as_ref().unwrap().script().to_vec()),
@@ -245,6 +349,7 @@ fn map_optional_transaction_inputs(
                     compute_budget: (include_compute_budget && input.compute_budget != Some(0))
                         .then(|| input.compute_budget.unwrap() as i16),
                     covenant_id: include_covenant_id.then(|| utxo.covenant_id.map(|v| v.into())).flatten(),
+                    signature_script_length,
                 }
             })
             .collect()
@@ -366,3 +471,75 @@ pub fn map_optional_transaction_outputs_script(transaction: &RpcOptionalTransact
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaspa_rpc_core::RpcSubnetworkId;
+    use std::str::FromStr;
+
+    const COINBASE: &str = "0100000000000000000000000000000000000000";
+    const NATIVE: &str = "0000000000000000000000000000000000000000";
+    const REGISTRY: &str = "0200000000000000000000000000000000000000";
+
+    fn subnetwork_id(hex: &str) -> RpcSubnetworkId {
+        RpcSubnetworkId::from_str(hex).unwrap()
+    }
+
+    #[test]
+    fn truncate_payload_caps_at_limit() {
+        let payload = vec![7u8; 1000];
+        assert_eq!(truncate_payload(&payload, 256), payload[..256]);
+        let (mapped, compression) = map_payload(&payload, 256, None);
+        assert_eq!(mapped.unwrap().len(), 256);
+        assert_eq!(compression, None);
+    }
+
+    #[test]
+    fn truncate_payload_zero_keeps_full_payload() {
+        let payload = vec![7u8; 1000];
+        assert_eq!(truncate_payload(&payload, 0), payload);
+        assert_eq!(map_payload(&payload, 0, None).0.unwrap(), payload);
+    }
+
+    #[test]
+    fn map_signature_script_at_cap_is_not_truncated() {
+        let script = vec![1u8; 64];
+        assert_eq!(map_signature_script(&script, Some(64)), (Some(script.clone()), None));
+    }
+
+    #[test]
+    fn map_signature_script_above_cap_is_truncated() {
+        let script = vec![1u8; 65];
+        assert_eq!(map_signature_script(&script, Some(64)), (Some(script[..64].to_vec()), Some(65)));
+    }
+
+    #[test]
+    fn map_signature_script_zero_keeps_full_script() {
+        let script = vec![1u8; 65];
+        assert_eq!(map_signature_script(&script, Some(0)), (Some(script.clone()), None));
+    }
+
+    #[test]
+    fn map_signature_script_none_excludes_script() {
+        assert_eq!(map_signature_script(&[1u8; 65], None), (None, None));
+    }
+
+    #[test]
+    fn is_coinbase_matches_only_coinbase_subnetwork() {
+        assert!(is_coinbase(&subnetwork_id(COINBASE)));
+        assert!(!is_coinbase(&subnetwork_id(NATIVE)));
+        assert!(!is_coinbase(&subnetwork_id(REGISTRY)));
+        assert!(!is_coinbase(&subnetwork_id("0100000000000000000000000000000000000001")));
+    }
+
+    #[test]
+    fn map_transaction_payload_drops_coinbase_payload() {
+        let payload_mapping = PayloadMapping { include: true, include_coinbase: false, max_bytes: 0, compress_min_bytes: None };
+        let payload = vec![7u8; 32];
+        assert_eq!(map_transaction_payload(&payload, &subnetwork_id(COINBASE), payload_mapping), (None, None));
+        assert_eq!(map_transaction_payload(&payload, &subnetwork_id(NATIVE), payload_mapping), (Some(payload.clone()), None));
+        let payload_mapping = PayloadMapping { include_coinbase: true, ..payload_mapping };
+        assert_eq!(map_transaction_payload(&payload, &subnetwork_id(COINBASE), payload_mapping), (Some(payload), None));
+    }
+}
@@ -1,4 +1,4 @@
-use log::warn;
+use log::{info, warn};
 use std::process;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -13,18 +13,25 @@ use tokio::sync::broadcast::Sender;
 pub struct SignalHandler {
     shutdown_tx: Sender<()>,
     shutdown_sent: Arc<AtomicBool>,
+    reload_tx: Sender<()>,
 }
 
 impl SignalHandler {
     pub fn new() -> SignalHandler {
         let (shutdown_tx, _) = broadcast::channel(1);
-        SignalHandler { shutdown_tx, shutdown_sent: Arc::new(AtomicBool::new(false)) }
+        let (reload_tx, _) = broadcast::channel(1);
+        SignalHandler { shutdown_tx, shutdown_sent: Arc::new(AtomicBool::new(false)), reload_tx }
     }
 
     pub fn subscribe(&self) -> broadcast::Receiver<()> {
         self.shutdown_tx.subscribe()
     }
 
+    /// Notified on SIGHUP (unix only)
+    pub fn subscribe_reload(&self) -> broadcast::Receiver<()> {
+        self.reload_tx.subscribe()
+    }
+
     pub fn is_shutdown(&self) -> bool {
         self.shutdown_sent.load(Ordering::Relaxed)
     }
@@ -42,6 +49,7 @@ impl SignalHandler {
         {
             let mut sigterm = signal(SignalKind::terminate()).expect("Failed to set up SIGTERM handler");
             let mut sigint = signal(SignalKind::interrupt()).expect("Failed to set up SIGINT handler");
+            let mut sighup = signal(SignalKind::hangup()).expect("Failed to set up SIGHUP handler");
             loop {
                 tokio::select! {
                     _ = sigint.recv() => {
@@ -50,6 +58,10 @@ impl SignalHandler {
                     _ = sigterm.recv() => {
                         self.handle_signal("SIGTERM");
                     },
+                    _ = sighup.recv() => {
+                        info!("SIGHUP received, reloading...");
+                        let _ = self.reload_tx.send(());
+                    },
                 }
             }
         }
@@ -65,6 +77,14 @@ impl SignalHandler {
         }
     }
 
+    /// Triggers a graceful shutdown, as if a signal was received
+    pub fn shutdown(&self, reason: &str) {
+        if !self.shutdown_sent.swap(true, Ordering::Relaxed) {
+            info!("{}, stopping...", reason);
+            let _ = self.shutdown_tx.send(());
+        }
+    }
+
     fn handle_signal(&self, signal: &str) {
         if self.shutdown_sent.load(Ordering::Relaxed) {
             warn!("{} received, terminating...", signal);